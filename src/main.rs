@@ -13,14 +13,14 @@ type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let input = read_to_string(args.file)?;
+    let input = read_to_string(&args.file)?;
     let input = format!("{{{input}}}");
 
     let mut parser = SplashParser;
 
     match parser.parse(&input) {
         Ok((_, program)) => {
-            if let Err(error) = Runtime::new(program).start() {
+            if let Err(error) = Runtime::new(program).path(args.file).start() {
                 eprintln!("Runtime error: {error}");
             }
         }
@@ -33,7 +33,7 @@ fn main() -> Result<()> {
                     None => eprintln!("Unexpected error: {error:?}"),
                 }
             }
-            nom::Err::Incomplete(_) => unreachable!(),
+            nom::Err::Incomplete(_) => eprintln!("Parse error: unexpected end of file."),
         },
     }
 
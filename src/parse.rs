@@ -8,10 +8,13 @@ pub use splash::SplashParser;
 
 pub use parsers::atom::Atom;
 pub use parsers::block::Block;
-pub use parsers::expression::Expression;
-pub use parsers::identifier::Identifier;
+pub use parsers::expression::{Accessor, Argument, Expression};
+pub use parsers::identifier::{is_capitalized, Identifier};
 pub use parsers::literal::Literal;
 pub use parsers::operation::{Operand, Operation};
 pub use parsers::operator::Operator;
+pub use parsers::parameter::Parameter;
+pub use parsers::pattern::Pattern;
 pub use parsers::program::Program;
-pub use parsers::statement::Statement;
+pub use parsers::statement::{Arm, Statement};
+pub use parsers::string::StringPart;
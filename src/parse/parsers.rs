@@ -7,6 +7,8 @@ pub mod keyword;
 pub mod literal;
 pub mod operation;
 pub mod operator;
+pub mod parameter;
+pub mod pattern;
 pub mod program;
 pub mod statement;
 pub mod string;
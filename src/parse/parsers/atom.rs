@@ -13,7 +13,7 @@ pub enum Atom<'a> {
     Identifier(Identifier<'a>),
 }
 
-pub fn atom(input: &str) -> IResult<&str, Atom, SplashParseError> {
+pub fn atom(input: &str) -> IResult<&str, Atom<'_>, SplashParseError<'_>> {
     alt((
         map(literal, Atom::Literal),
         map(identifier, Atom::Identifier),
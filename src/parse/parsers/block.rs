@@ -9,7 +9,7 @@ use super::statement::statement;
 #[derive(Clone, Debug)]
 pub struct Block<'a>(Vec<Statement<'a>>);
 
-pub fn block(input: &str) -> IResult<&str, Block, SplashParseError> {
+pub fn block(input: &str) -> IResult<&str, Block<'_>, SplashParseError<'_>> {
     map(
         delimited(char('{'), many0(trim(statement)), char('}')),
         Block,
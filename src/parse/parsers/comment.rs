@@ -8,7 +8,7 @@ use nom::{
 
 use crate::parse::SplashParseError;
 
-pub fn comment(input: &str) -> IResult<&str, (), SplashParseError> {
+pub fn comment(input: &str) -> IResult<&str, (), SplashParseError<'_>> {
     value(
         (), // Output is thrown away.
         alt((
@@ -1,49 +1,128 @@
 use nom::{
     branch::alt,
-    character::complete::char,
-    combinator::map,
-    multi::separated_list0,
-    sequence::{delimited, tuple},
+    bytes::complete::tag,
+    character::complete::{char, multispace0},
+    combinator::{map, not, verify},
+    multi::{fold_many0, many1, separated_list0},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
     IResult, Parser,
 };
 
-use crate::parse::{combinators::trim::trim, SplashParseError};
+use crate::parse::{
+    combinators::{parentheses::parentheses, trim::trim},
+    SplashParseError,
+};
 
 use super::{
     atom::{atom, Atom},
-    identifier::{identifier, Identifier},
-    operation::{operation, Operation},
+    block::{block, Block},
+    identifier::{identifier, is_capitalized, Identifier},
+    keyword::{keyword, Keyword},
+    operation::{operation_or_operand, Operand, Operation},
+    parameter::{parameters, Parameter},
+    string::{interpolated_string, StringPart},
 };
 
 #[derive(Clone, Debug)]
 pub enum Expression<'a> {
     Atom(Atom<'a>),
     Operation(Operation<'a>),
-    Function(Identifier<'a>, Vec<Expression<'a>>),
+    Function(Identifier<'a>, Vec<Argument<'a>>),
+    /// A call of the function another expression evaluates to, such as `make_counter()()`.
+    Call(Box<Expression<'a>>, Vec<Argument<'a>>),
     List(Vec<Expression<'a>>),
-    Index(Identifier<'a>, Box<Expression<'a>>),
+    Map(Vec<(Expression<'a>, Expression<'a>)>),
+    Index(Box<Expression<'a>>, Box<Expression<'a>>),
+    Method(Box<Expression<'a>>, Identifier<'a>, Vec<Argument<'a>>),
+    Field(Box<Expression<'a>>, Identifier<'a>),
+    Construction(Identifier<'a>, Vec<(Identifier<'a>, Expression<'a>)>),
+    Lambda(Vec<Parameter<'a>>, Block<'a>),
+    /// A string embedding the values of expressions.
+    Interpolation(Vec<StringPart<'a>>),
+}
+
+fn parse_atom(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    map(atom, Expression::Atom).parse(input)
+}
+
+/// An argument of a call, which is either given by position or by the name of its parameter, as in `f(x = 1)`.
+#[derive(Clone, Debug)]
+pub enum Argument<'a> {
+    Positional(Expression<'a>),
+    Keyword(Identifier<'a>, Expression<'a>),
+}
+
+/// A step into a value, which can be assigned to.
+#[derive(Clone, Debug)]
+pub enum Accessor<'a> {
+    Index(Expression<'a>),
+    Field(Identifier<'a>),
 }
 
-fn parse_atom(input: &str) -> IResult<&str, Expression, SplashParseError> {
-    map(trim(atom), Expression::Atom).parse(input)
+/// Postfix operations that can follow an expression.
+enum Postfix<'a> {
+    Index(Expression<'a>),
+    Call(Vec<Argument<'a>>),
+    Method(Identifier<'a>, Vec<Argument<'a>>),
+    Field(Identifier<'a>),
 }
 
-fn parse_function(input: &str) -> IResult<&str, Expression, SplashParseError> {
+/// The `=` of a keyword argument must not start a `==` comparison.
+fn parse_argument(input: &str) -> IResult<&str, Argument<'_>, SplashParseError<'_>> {
+    alt((
+        map(
+            separated_pair(
+                identifier,
+                trim(terminated(char('='), not(char('=')))),
+                expression,
+            ),
+            |(name, value)| Argument::Keyword(name, value),
+        ),
+        map(expression, Argument::Positional),
+    ))
+    .parse(input)
+}
+
+fn parse_arguments(input: &str) -> IResult<&str, Vec<Argument<'_>>, SplashParseError<'_>> {
+    delimited(
+        char('('),
+        separated_list0(char(','), trim(parse_argument)),
+        char(')'),
+    )
+    .parse(input)
+}
+
+fn parse_function(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    map(
+        tuple((identifier, parse_arguments)),
+        |(name, parameters)| Expression::Function(name, parameters),
+    )
+    .parse(input)
+}
+
+/// The name of the structure must be capitalized, so that the block of a condition such as `if ready { y: 1 }`
+/// isn't read as the fields of a construction.
+fn parse_construction(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    let name = verify(identifier, |name: &str| is_capitalized(name));
+
     map(
         tuple((
-            identifier,
+            terminated(name, multispace0),
             delimited(
-                char('('),
-                separated_list0(char(','), trim(expression)),
-                char(')'),
+                char('{'),
+                separated_list0(
+                    char(','),
+                    separated_pair(trim(identifier), char(':'), trim(expression)),
+                ),
+                preceded(multispace0, char('}')),
             ),
         )),
-        |(name, parameters)| Expression::Function(name, parameters),
+        |(name, fields)| Expression::Construction(name, fields),
     )
     .parse(input)
 }
 
-fn parse_list(input: &str) -> IResult<&str, Expression, SplashParseError> {
+fn parse_list(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
     map(
         delimited(
             char('['),
@@ -55,25 +134,157 @@ fn parse_list(input: &str) -> IResult<&str, Expression, SplashParseError> {
     .parse(input)
 }
 
-fn parse_index(input: &str) -> IResult<&str, Expression, SplashParseError> {
+fn parse_map(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    map(
+        delimited(
+            tag("#{"),
+            separated_list0(
+                char(','),
+                separated_pair(trim(expression), char(':'), trim(expression)),
+            ),
+            char('}'),
+        ),
+        Expression::Map,
+    )
+    .parse(input)
+}
+
+pub fn index(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    delimited(char('['), trim(expression), char(']')).parse(input)
+}
+
+fn parse_lambda(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
     map(
         tuple((
-            identifier,
-            delimited(char('['), trim(expression), char(']')),
+            preceded(keyword(Keyword::Fn), trim(parameters)),
+            trim(block),
         )),
-        |(identifier, index)| Expression::Index(identifier, Box::new(index)),
+        |(arguments, body)| Expression::Lambda(arguments, body),
     )
     .parse(input)
 }
 
-pub fn expression_no_operation(input: &str) -> IResult<&str, Expression, SplashParseError> {
-    alt((parse_index, parse_list, parse_function, parse_atom)).parse(input)
+fn parse_interpolation(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    map(interpolated_string, Expression::Interpolation).parse(input)
 }
 
-pub fn expression(input: &str) -> IResult<&str, Expression, SplashParseError> {
+/// Strings without interpolation are parsed as atoms, so that only strings with braces need to be evaluated.
+fn parse_primary(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
     alt((
-        map(operation, Expression::Operation),
-        expression_no_operation,
+        parse_lambda,
+        parse_list,
+        parse_map,
+        parse_construction,
+        parse_function,
+        parse_atom,
+        parse_interpolation,
+        parentheses(trim(expression)),
     ))
     .parse(input)
 }
+
+fn parse_method(input: &str) -> IResult<&str, Postfix<'_>, SplashParseError<'_>> {
+    map(
+        preceded(
+            tuple((multispace0, char('.'))),
+            tuple((trim(identifier), parse_arguments)),
+        ),
+        |(name, arguments)| Postfix::Method(name, arguments),
+    )
+    .parse(input)
+}
+
+pub fn field(input: &str) -> IResult<&str, Identifier<'_>, SplashParseError<'_>> {
+    preceded(tuple((multispace0, char('.'), multispace0)), identifier).parse(input)
+}
+
+/// Parses the accessors of an assignment target, such as `[i].field[j]`.
+pub fn accessors(input: &str) -> IResult<&str, Vec<Accessor<'_>>, SplashParseError<'_>> {
+    many1(alt((
+        map(index, Accessor::Index),
+        map(field, Accessor::Field),
+    )))
+    .parse(input)
+}
+
+/// Indices and arguments must directly follow what they index or call, so that a list or a parenthesized
+/// expression on the next line isn't mistaken for one. Method calls however can be chained across lines.
+fn parse_postfix(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    let (input, primary) = parse_primary(input)?;
+
+    fold_many0(
+        alt((
+            map(index, Postfix::Index),
+            map(parse_arguments, Postfix::Call),
+            parse_method,
+            map(field, Postfix::Field),
+        )),
+        move || primary.clone(),
+        |expression, postfix| match postfix {
+            Postfix::Index(index) => Expression::Index(Box::new(expression), Box::new(index)),
+            Postfix::Call(arguments) => Expression::Call(Box::new(expression), arguments),
+            Postfix::Method(name, arguments) => {
+                Expression::Method(Box::new(expression), name, arguments)
+            }
+            Postfix::Field(name) => Expression::Field(Box::new(expression), name),
+        },
+    )
+    .parse(input)
+}
+
+pub fn expression_no_operation(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    parse_postfix(input)
+}
+
+pub fn expression(input: &str) -> IResult<&str, Expression<'_>, SplashParseError<'_>> {
+    map(operation_or_operand, |operand| match operand {
+        Operand::Operation(operation) => Expression::Operation(operation),
+        Operand::Expression(expression) => expression,
+    })
+    .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expression, Expression};
+
+    fn parse(input: &str) -> Expression<'_> {
+        let (rest, expression) = expression(input).unwrap();
+        assert!(rest.is_empty(), "unparsed input: {rest:?}");
+        expression
+    }
+
+    #[test]
+    fn postfixes_of_parenthesized_expressions() {
+        assert!(matches!(
+            parse("(0..5)[1]"),
+            Expression::Index(collection, _) if matches!(*collection, Expression::Operation(_))
+        ));
+        assert!(matches!(
+            parse("(a + b).abs()"),
+            Expression::Method(receiver, "abs", _) if matches!(*receiver, Expression::Operation(_))
+        ));
+        assert!(matches!(
+            parse("(a + b).abs() * 2"),
+            Expression::Operation(_)
+        ));
+        assert!(matches!(
+            parse("(point).x"),
+            Expression::Field(instance, "x") if matches!(*instance, Expression::Atom(_))
+        ));
+    }
+
+    #[test]
+    fn calls_of_any_expression() {
+        assert!(matches!(
+            parse("make_counter()()"),
+            Expression::Call(function, arguments)
+                if arguments.is_empty() && matches!(*function, Expression::Function("make_counter", _))
+        ));
+        assert!(matches!(
+            parse("fs[1](2)"),
+            Expression::Call(function, arguments)
+                if arguments.len() == 1 && matches!(*function, Expression::Index(..))
+        ));
+    }
+}
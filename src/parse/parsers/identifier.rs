@@ -2,7 +2,7 @@ use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::{alpha1, alphanumeric1},
-    combinator::recognize,
+    combinator::{recognize, verify},
     multi::many0_count,
     sequence::pair,
     IResult, Parser,
@@ -10,12 +10,22 @@ use nom::{
 
 use crate::parse::SplashParseError;
 
+use super::keyword::is_keyword;
+
 pub type Identifier<'a> = &'a str;
 
-pub fn identifier(input: &str) -> IResult<&str, Identifier<'_>, SplashParseError> {
-    recognize(pair(
+/// Whether an identifier starts with a capital letter, as the names of structures must.
+#[must_use]
+pub fn is_capitalized(identifier: Identifier) -> bool {
+    identifier.starts_with(|character: char| character.is_ascii_uppercase())
+}
+
+/// Keywords aren't identifiers, so that statements such as `let [x] = [1]` can't be read as using a variable.
+pub fn identifier(input: &str) -> IResult<&str, Identifier<'_>, SplashParseError<'_>> {
+    let word = recognize(pair(
         alt((alpha1, tag("_"))),
         many0_count(alt((alphanumeric1, tag("_")))),
-    ))
-    .parse(input)
+    ));
+
+    verify(word, |word: &str| !is_keyword(word)).parse(input)
 }
@@ -4,6 +4,7 @@ use crate::parse::SplashParseError;
 
 pub enum Keyword {
     Let,
+    Const,
     If,
     Else,
     While,
@@ -11,12 +12,49 @@ pub enum Keyword {
     In,
     Return,
     Fn,
+    Break,
+    Continue,
+    Struct,
+    Enum,
+    Match,
+    Try,
+    Catch,
+    Finally,
+    Throw,
+    Import,
+    From,
+    As,
 }
 
 impl Keyword {
+    const ALL: [Self; 21] = [
+        Self::Let,
+        Self::Const,
+        Self::If,
+        Self::Else,
+        Self::While,
+        Self::For,
+        Self::In,
+        Self::Return,
+        Self::Fn,
+        Self::Break,
+        Self::Continue,
+        Self::Struct,
+        Self::Enum,
+        Self::Match,
+        Self::Try,
+        Self::Catch,
+        Self::Finally,
+        Self::Throw,
+        Self::Import,
+        Self::From,
+        Self::As,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             Self::Let => "let",
+            Self::Const => "const",
             Self::If => "if",
             Self::Else => "else",
             Self::While => "while",
@@ -24,15 +62,38 @@ impl Keyword {
             Self::In => "in",
             Self::Return => "return",
             Self::Fn => "fn",
+            Self::Break => "break",
+            Self::Continue => "continue",
+            Self::Struct => "struct",
+            Self::Enum => "enum",
+            Self::Match => "match",
+            Self::Try => "try",
+            Self::Catch => "catch",
+            Self::Finally => "finally",
+            Self::Throw => "throw",
+            Self::Import => "import",
+            Self::From => "from",
+            Self::As => "as",
         }
     }
 }
 
+/// Whether `word` is reserved, and so can't be used as an identifier.
+pub fn is_keyword(word: &str) -> bool {
+    Keyword::ALL.iter().any(|keyword| keyword.as_str() == word)
+}
+
+/// Parses `keyword`, as long as it isn't only the beginning of a longer identifier.
 pub fn keyword<'a>(keyword: Keyword) -> impl Parser<&'a str, &'a str, SplashParseError<'a>> {
     move |input: &'a str| {
         let keyword = keyword.as_str();
         let res: IResult<_, _, SplashParseError> = match input.compare(keyword) {
-            CompareResult::Ok => Ok(input.take_split(keyword.len())),
+            CompareResult::Ok
+                if !input[keyword.len()..]
+                    .starts_with(|c: char| c.is_alphanumeric() || c == '_') =>
+            {
+                Ok(input.take_split(keyword.len()))
+            }
             _ => Err(Err::Error(SplashParseError::InvalidKeyword)),
         };
         res
@@ -1,38 +1,116 @@
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    combinator::{map, value},
-    number::complete::double,
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{char, digit1, one_of},
+    combinator::{map, map_opt, not, opt, recognize, value, verify},
+    sequence::{pair, terminated, tuple},
     IResult, Parser,
 };
 
 use crate::parse::SplashParseError;
 
-use super::string::string;
+use super::{identifier::identifier, string::string};
 
 #[derive(Clone, Debug)]
 pub enum Literal {
+    Nil,
+    Integer(i64),
     Number(f64),
     Boolean(bool),
     String(String),
 }
 
-fn parse_number(input: &str) -> IResult<&str, Literal, SplashParseError> {
-    map(double, Literal::Number).parse(input)
+/// The text of a number, which has no sign so that `-2 ** 2` negates the power. A fractional part needs digits
+/// after the dot, so that methods can be called on integers.
+fn number(input: &str) -> IResult<&str, &str, SplashParseError<'_>> {
+    recognize(tuple((
+        digit1,
+        opt(pair(char('.'), digit1)),
+        opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+    )))
+    .parse(input)
 }
 
-fn parse_boolean(input: &str) -> IResult<&str, Literal, SplashParseError> {
-    alt((
-        value(Literal::Boolean(true), tag("true")),
-        value(Literal::Boolean(false), tag("false")),
-    ))
+/// Numbers without a fractional part or an exponent are integers, unless they are too large for an integer
+/// and are approximated by a float instead.
+fn convert_number(number: &str) -> Option<Literal> {
+    if !number.contains(['.', 'e', 'E']) {
+        if let Ok(integer) = number.parse() {
+            return Some(Literal::Integer(integer));
+        }
+    }
+
+    number.parse().ok().map(Literal::Number)
+}
+
+fn parse_number(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    map_opt(number, convert_number).parse(input)
+}
+
+fn parse_negative_number(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    map_opt(recognize(pair(char('-'), number)), convert_number).parse(input)
+}
+
+/// The smallest integer, which is the only negative literal outside of patterns since its magnitude is too
+/// large to be negated.
+pub fn minimum_integer(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    verify(parse_negative_number, |literal| {
+        matches!(literal, Literal::Integer(i64::MIN))
+    })
+    .parse(input)
+}
+
+/// Infinity and NaN, which must not be the beginning of an identifier. The capitalized `INF` and `NAN` are
+/// left to the constants of the prelude, which can be shadowed.
+fn parse_special_number(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    let word = terminated(
+        alt((
+            tag_no_case("infinity"),
+            tag_no_case("inf"),
+            tag_no_case("nan"),
+        )),
+        not(identifier),
+    );
+
+    map_opt(
+        verify(word, |word: &str| !matches!(word, "INF" | "NAN")),
+        |number: &str| number.parse().ok().map(Literal::Number),
+    )
+    .parse(input)
+}
+
+fn parse_nil(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    value(Literal::Nil, terminated(tag("nil"), not(identifier))).parse(input)
+}
+
+fn parse_boolean(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    terminated(
+        alt((
+            value(Literal::Boolean(true), tag("true")),
+            value(Literal::Boolean(false), tag("false")),
+        )),
+        not(identifier),
+    )
     .parse(input)
 }
 
-fn parse_string(input: &str) -> IResult<&str, Literal, SplashParseError> {
+fn parse_string(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
     map(string, Literal::String).parse(input)
 }
 
-pub fn literal(input: &str) -> IResult<&str, Literal, SplashParseError> {
-    alt((parse_number, parse_boolean, parse_string)).parse(input)
+pub fn literal(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    alt((
+        parse_special_number,
+        parse_number,
+        minimum_integer,
+        parse_nil,
+        parse_boolean,
+        parse_string,
+    ))
+    .parse(input)
+}
+
+/// Literals in patterns, where numbers can be negative.
+pub fn pattern_literal(input: &str) -> IResult<&str, Literal, SplashParseError<'_>> {
+    alt((parse_negative_number, literal)).parse(input)
 }
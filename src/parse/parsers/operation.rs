@@ -1,14 +1,24 @@
-use std::iter::{Peekable, Rev};
+use std::iter::Peekable;
 
-use nom::{branch::alt, combinator::map, multi::many1, sequence::tuple, IResult, Parser};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::multispace0,
+    combinator::{map, not, opt, value},
+    multi::many0,
+    sequence::{preceded, tuple},
+    IResult, Parser,
+};
 
 use crate::parse::{
-    combinators::{parentheses::parentheses, trim::trim},
+    combinators::{trim::trim, whitespace::whitespace},
     Expression, Operator, SplashParseError,
 };
 
 use super::{
+    comment::comment,
     expression::expression_no_operation,
+    literal::minimum_integer,
     operator::{binary_operator, unary_operator},
 };
 
@@ -40,71 +50,198 @@ impl<'a> Operation<'a> {
     }
 }
 
-fn parse_operand(input: &str) -> IResult<&str, Operand, SplashParseError> {
+/// Parenthesized operations are expressions, so that they can be indexed or have methods called on them.
+fn parse_operand(input: &str) -> IResult<&str, Operand<'_>, SplashParseError<'_>> {
     alt((
-        map(
-            trim(alt((
-                parse_unary_operation,
-                parentheses(trim(parse_multi_operation)),
-            ))),
-            Operand::Operation,
-        ),
+        map(trim(parse_unary_operation), Operand::Operation),
         map(trim(expression_no_operation), Operand::Expression),
     ))
     .parse(input)
 }
 
-fn parse_unary_operation(input: &str) -> IResult<&str, Operation, SplashParseError> {
+/// An operand followed by any number of powers, which bind tighter than the unary operator before them.
+fn parse_power(input: &str) -> IResult<&str, Operand<'_>, SplashParseError<'_>> {
+    map(
+        tuple((parse_operand, opt(preceded(trim(tag("**")), parse_power)))),
+        |(base, exponent)| match exponent {
+            Some(exponent) => {
+                Operand::Operation(Operation::new(Operator::Power, vec![base, exponent]))
+            }
+            None => base,
+        },
+    )
+    .parse(input)
+}
+
+/// The smallest integer is a literal rather than a negation, as its magnitude doesn't fit in an integer.
+fn parse_unary_operation(input: &str) -> IResult<&str, Operation<'_>, SplashParseError<'_>> {
     map(
-        tuple((unary_operator, trim(parse_operand))),
+        tuple((
+            preceded(not(minimum_integer), unary_operator),
+            trim(parse_power),
+        )),
         |(operator, operand)| Operation::new(operator, vec![operand]),
     )
     .parse(input)
 }
 
-fn transform_multi_operation<'a>(
-    mut others: Peekable<Rev<std::vec::IntoIter<(Operand<'a>, Operator)>>>,
-    right: Operand<'a>,
-) -> Operation<'a> {
-    match others.next() {
-        Some((left, operator1)) => match others.peek() {
-            Some((_, operator2)) => {
-                if operator1.priority() < operator2.priority() {
-                    Operation::new(
-                        operator1,
-                        vec![
-                            Operand::Operation(transform_multi_operation(others, left)),
-                            right,
-                        ],
-                    )
-                } else {
-                    let operation = Operation::new(operator1, vec![left, right]);
-                    transform_multi_operation(others, Operand::Operation(operation))
-                }
+/// Groups the `left` operand with the operators and operands that follow it, as long as the operators have
+/// at least the `minimum` priority. Operators of higher priority are grouped first, and operators of equal
+/// priority are grouped from left to right, unless they are right-associative.
+fn climb<'a>(
+    mut left: Operand<'a>,
+    rest: &mut Peekable<impl Iterator<Item = (Operator, Operand<'a>)>>,
+    minimum: u8,
+) -> Operand<'a> {
+    while let Some((operator, mut right)) =
+        rest.next_if(|(operator, _)| operator.priority() >= minimum)
+    {
+        while let Some((next, _)) = rest.peek() {
+            let tighter = next.priority() > operator.priority()
+                || (next.priority() == operator.priority() && next.is_right_associative());
+            if !tighter {
+                break;
             }
-            None => Operation::new(operator1, vec![left, right]),
-        },
-        None => match right {
-            Operand::Operation(operation) => operation,
-            Operand::Expression(_) => unreachable!("expressions shouldn't appear alone here"),
-        },
+
+            let priority = next.priority();
+            right = climb(right, rest, priority);
+        }
+
+        left = Operand::Operation(Operation::new(operator, vec![left, right]));
     }
+
+    left
 }
 
-fn parse_multi_operation(input: &str) -> IResult<&str, Operation, SplashParseError> {
-    alt((
-        map(
-            tuple((
-                many1(tuple((trim(parse_operand), binary_operator))),
+/// Skips the whitespace and comments before an operator or an operand.
+fn space(input: &str) -> IResult<&str, (), SplashParseError<'_>> {
+    value((), tuple((many0(whitespace(comment)), multispace0))).parse(input)
+}
+
+/// An operation, or its first operand alone if no operator follows it. The first operand is only parsed once,
+/// since parsing it again as a plain expression would take exponential time in nested parentheses. The
+/// whitespace after a lone operand is left unparsed.
+pub fn operation_or_operand(input: &str) -> IResult<&str, Operand<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            preceded(
+                space,
+                alt((
+                    map(parse_unary_operation, Operand::Operation),
+                    map(expression_no_operation, Operand::Expression),
+                )),
+            ),
+            many0(tuple((
+                preceded(space, binary_operator),
                 trim(parse_operand),
-            )),
-            |(others, right)| transform_multi_operation(others.into_iter().rev().peekable(), right),
-        ),
-        trim(parse_unary_operation),
-    ))
+            ))),
+        )),
+        // Each operator is paired with the operand that follows it.
+        |(first, rest)| climb(first, &mut rest.into_iter().peekable(), 0),
+    )
     .parse(input)
 }
 
-pub fn operation(input: &str) -> IResult<&str, Operation, SplashParseError> {
-    parse_multi_operation(input)
+#[cfg(test)]
+mod tests {
+    use crate::parse::{Atom, Expression, Literal};
+
+    use super::{operation_or_operand, Operand, Operation};
+
+    /// Writes an operation with every grouping made explicit, such as `((a Minus b) Minus c)`.
+    fn shape(operation: &Operation) -> String {
+        let operands = operation
+            .operands()
+            .iter()
+            .map(|operand| match operand {
+                Operand::Operation(operation)
+                | Operand::Expression(Expression::Operation(operation)) => shape(operation),
+                Operand::Expression(Expression::Atom(Atom::Identifier(identifier))) => {
+                    (*identifier).to_string()
+                }
+                Operand::Expression(Expression::Atom(Atom::Literal(Literal::Integer(integer)))) => {
+                    integer.to_string()
+                }
+                Operand::Expression(expression) => format!("{expression:?}"),
+            })
+            .collect::<Vec<_>>();
+
+        match operands.as_slice() {
+            [operand] => format!("({:?} {operand})", operation.operator()),
+            [left, right] => format!("({left} {:?} {right})", operation.operator()),
+            _ => unreachable!("operations have one or two operands"),
+        }
+    }
+
+    fn parse(input: &str) -> String {
+        let (rest, operand) = operation_or_operand(input).expect("the operation should parse");
+        assert_eq!(rest, "");
+        match operand {
+            Operand::Operation(operation) => shape(&operation),
+            Operand::Expression(expression) => panic!("{expression:?} isn't an operation"),
+        }
+    }
+
+    #[test]
+    fn left_associativity() {
+        assert_eq!(parse("a - b - c"), "((a Minus b) Minus c)");
+        assert_eq!(parse("a / b * c"), "((a Divide b) Times c)");
+
+        assert_eq!(parse("a && b && c"), "((a And b) And c)");
+    }
+
+    #[test]
+    fn right_associativity() {
+        assert_eq!(parse("a ** b ** c"), "(a Power (b Power c))");
+        assert_eq!(parse("a ** b ** c * d"), "((a Power (b Power c)) Times d)");
+    }
+
+    #[test]
+    fn arithmetic_precedence() {
+        assert_eq!(parse("a + b * c"), "(a Plus (b Times c))");
+        assert_eq!(parse("a * b + c * d"), "((a Times b) Plus (c Times d))");
+        assert_eq!(parse("a + b * c + d"), "((a Plus (b Times c)) Plus d)");
+        assert_eq!(parse("a % b + c"), "((a Modulo b) Plus c)");
+        assert_eq!(parse("a * b ** c"), "(a Times (b Power c))");
+    }
+
+    #[test]
+    fn logical_precedence() {
+        assert_eq!(parse("a || b && c"), "(a Or (b And c))");
+        assert_eq!(parse("a && b || c"), "((a And b) Or c)");
+        assert_eq!(parse("a < b && c == d"), "((a LessThan b) And (c Equal d))");
+        assert_eq!(parse("a + 1 < b * 2"), "((a Plus 1) LessThan (b Times 2))");
+        assert_eq!(parse("a ?? b || c"), "(a Coalesce (b Or c))");
+    }
+
+    #[test]
+    fn bitwise_precedence() {
+        assert_eq!(parse("a | b ^ c & d"), "(a BitOr (b BitXor (c BitAnd d)))");
+        assert_eq!(parse("a << b + c"), "(a ShiftLeft (b Plus c))");
+        assert_eq!(parse("a & b == c"), "((a BitAnd b) Equal c)");
+    }
+
+    #[test]
+    fn range_precedence() {
+        assert_eq!(parse("a..b + 1"), "(a Range (b Plus 1))");
+        assert_eq!(parse("0..=a | b"), "(0 RangeInclusive (a BitOr b))");
+        assert_eq!(parse("a..b == c"), "((a Range b) Equal c)");
+    }
+
+    #[test]
+    fn unary_and_parentheses() {
+        assert_eq!(parse("!a && b"), "((Not a) And b)");
+        assert_eq!(parse("-a * b"), "((Negate a) Times b)");
+        assert_eq!(parse("-a ** b"), "(Negate (a Power b))");
+        assert_eq!(parse("-a ** b ** c"), "(Negate (a Power (b Power c)))");
+        assert_eq!(parse("a ** -b"), "(a Power (Negate b))");
+        assert_eq!(parse("-2 ** 2"), "(Negate (2 Power 2))");
+        assert_eq!(
+            parse("-9223372036854775808 + 1"),
+            "(-9223372036854775808 Plus 1)"
+        );
+        assert_eq!(parse("!a ** b * c"), "((Not (a Power b)) Times c)");
+        assert_eq!(parse("(a + b) * c"), "((a Plus b) Times c)");
+        assert_eq!(parse("a - (b - c)"), "(a Minus (b Minus c))");
+    }
 }
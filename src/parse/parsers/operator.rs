@@ -1,18 +1,20 @@
 use nom::{
     branch::alt, bytes::complete::tag, character::complete::char, combinator::value,
-    error::ParseError, IResult, Parser,
+    error::ParseError, sequence::terminated, IResult, Parser,
 };
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum Operator {
     // Unary
     Not,
+    Negate,
 
     // Binary
     Plus,
     Minus,
     Times,
     Divide,
+    Power,
     Modulo,
     Equal,
     NotEqual,
@@ -22,50 +24,112 @@ pub enum Operator {
     LessThan,
     And,
     Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    Range,
+    RangeInclusive,
+    Coalesce,
 }
 
 impl Operator {
+    /// How tightly the operator binds its operands: higher priorities are grouped first. Powers bind tighter than
+    /// unary operators, so that `-a ** b` is `-(a ** b)`.
     #[must_use]
-    pub fn priority(&self) -> impl Ord {
+    pub fn priority(&self) -> u8 {
         match self {
+            Self::Power => 12,
+
             // Unary
-            Self::Not => 4,
+            Self::Not | Self::Negate => 11,
 
             // Binary
-            Self::Times | Self::Divide | Self::And => 3,
-            Self::Plus | Self::Minus | Self::Or => 2,
-            Self::Modulo => 1,
+            Self::Times | Self::Divide | Self::Modulo => 10,
+            Self::Plus | Self::Minus => 9,
+            Self::ShiftLeft | Self::ShiftRight => 8,
+            Self::BitAnd => 7,
+            Self::BitXor => 6,
+            Self::BitOr => 5,
+            Self::Range | Self::RangeInclusive => 4,
             Self::Equal
             | Self::NotEqual
             | Self::GreaterOrEqual
             | Self::GreaterThan
             | Self::LessOrEqual
-            | Self::LessThan => 0,
+            | Self::LessThan => 3,
+            Self::And => 2,
+            Self::Or => 1,
+            Self::Coalesce => 0,
         }
     }
+
+    /// Whether consecutive uses of the operator are grouped from right to left, as in `a ** (b ** c)`.
+    #[must_use]
+    pub fn is_right_associative(&self) -> bool {
+        matches!(self, Self::Power)
+    }
 }
 
 pub fn unary_operator<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, Operator, E> {
-    value(Operator::Not, char('!')).parse(input)
+    alt((
+        value(Operator::Not, char('!')),
+        value(Operator::Negate, char('-')),
+    ))
+    .parse(input)
 }
 
+/// Operators sharing a prefix with a shorter one, such as `<<` and `<`, are tried first.
 pub fn binary_operator<'a, E: ParseError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, Operator, E> {
     alt((
-        value(Operator::Plus, char('+')),
-        value(Operator::Minus, char('-')),
-        value(Operator::Times, char('*')),
-        value(Operator::Divide, char('/')),
-        value(Operator::Modulo, char('%')),
-        value(Operator::Equal, tag("==")),
-        value(Operator::NotEqual, tag("!=")),
-        value(Operator::GreaterOrEqual, tag(">=")),
-        value(Operator::GreaterThan, char('>')),
-        value(Operator::LessOrEqual, tag("<=")),
-        value(Operator::LessThan, char('<')),
-        value(Operator::And, tag("&&")),
-        value(Operator::Or, tag("||")),
+        alt((
+            value(Operator::Plus, char('+')),
+            value(Operator::Minus, char('-')),
+            value(Operator::Power, tag("**")),
+            value(Operator::Times, char('*')),
+            value(Operator::Divide, char('/')),
+            value(Operator::Modulo, char('%')),
+        )),
+        alt((
+            value(Operator::Equal, tag("==")),
+            value(Operator::NotEqual, tag("!=")),
+            value(Operator::ShiftRight, tag(">>")),
+            value(Operator::GreaterOrEqual, tag(">=")),
+            value(Operator::GreaterThan, char('>')),
+            value(Operator::ShiftLeft, tag("<<")),
+            value(Operator::LessOrEqual, tag("<=")),
+            value(Operator::LessThan, char('<')),
+        )),
+        alt((
+            value(Operator::And, tag("&&")),
+            value(Operator::Or, tag("||")),
+            value(Operator::BitAnd, char('&')),
+            value(Operator::BitOr, char('|')),
+            value(Operator::BitXor, char('^')),
+            value(Operator::RangeInclusive, tag("..=")),
+            value(Operator::Range, tag("..")),
+            value(Operator::Coalesce, tag("??")),
+        )),
     ))
     .parse(input)
 }
+
+/// Parses the operator of a compound assignment, such as `+=`.
+pub fn compound_operator<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, Operator, E> {
+    terminated(
+        alt((
+            value(Operator::Plus, char('+')),
+            value(Operator::Minus, char('-')),
+            value(Operator::Times, char('*')),
+            value(Operator::Divide, char('/')),
+            value(Operator::Modulo, char('%')),
+        )),
+        char('='),
+    )
+    .parse(input)
+}
@@ -0,0 +1,93 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::char,
+    combinator::{map, opt, verify},
+    multi::separated_list0,
+    sequence::{delimited, pair, preceded},
+    IResult, Parser,
+};
+
+use crate::parse::{combinators::trim::trim, SplashParseError};
+
+use super::{
+    expression::{expression, Expression},
+    identifier::{identifier, Identifier},
+};
+
+/// A parameter of a function, which is either required, optional with a default value, or variadic.
+#[derive(Clone, Debug)]
+pub struct Parameter<'a> {
+    name: Identifier<'a>,
+    default: Option<Expression<'a>>,
+    variadic: bool,
+}
+
+impl<'a> Parameter<'a> {
+    #[must_use]
+    pub fn name(&self) -> Identifier<'a> {
+        self.name
+    }
+
+    /// The expression evaluated when no argument is given for the parameter.
+    #[must_use]
+    pub fn default(&self) -> Option<&Expression<'a>> {
+        self.default.as_ref()
+    }
+
+    /// Whether the parameter collects the extra positional arguments into a list.
+    #[must_use]
+    pub fn variadic(&self) -> bool {
+        self.variadic
+    }
+}
+
+fn parse_variadic(input: &str) -> IResult<&str, Parameter<'_>, SplashParseError<'_>> {
+    map(preceded(tag(".."), identifier), |name| Parameter {
+        name,
+        default: None,
+        variadic: true,
+    })
+    .parse(input)
+}
+
+fn parse_parameter(input: &str) -> IResult<&str, Parameter<'_>, SplashParseError<'_>> {
+    map(
+        pair(identifier, opt(preceded(trim(char('=')), expression))),
+        |(name, default)| Parameter {
+            name,
+            default,
+            variadic: false,
+        },
+    )
+    .parse(input)
+}
+
+/// Required parameters come first, then parameters with a default value, then an optional variadic parameter.
+fn is_ordered(parameters: &[Parameter]) -> bool {
+    let variadic = parameters.iter().position(Parameter::variadic);
+    if variadic.is_some_and(|position| position != parameters.len() - 1) {
+        return false;
+    }
+
+    let positional = &parameters[..variadic.unwrap_or(parameters.len())];
+    let required = positional
+        .iter()
+        .take_while(|parameter| parameter.default.is_none())
+        .count();
+    positional[required..]
+        .iter()
+        .all(|parameter| parameter.default.is_some())
+}
+
+pub fn parameters(input: &str) -> IResult<&str, Vec<Parameter<'_>>, SplashParseError<'_>> {
+    delimited(
+        char('('),
+        verify(
+            separated_list0(char(','), trim(alt((parse_variadic, parse_parameter)))),
+            |parameters: &[Parameter]| is_ordered(parameters),
+        ),
+        char(')'),
+    )
+    .parse(input)
+}
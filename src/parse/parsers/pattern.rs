@@ -0,0 +1,139 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, multispace0},
+    combinator::{map, opt, verify},
+    multi::separated_list0,
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+    IResult, Parser,
+};
+
+use crate::parse::{combinators::trim::trim, SplashParseError};
+
+use super::{
+    identifier::{identifier, Identifier},
+    literal::{pattern_literal, Literal},
+};
+
+#[derive(Clone, Debug)]
+pub enum Pattern<'a> {
+    Wildcard,
+    Literal(Literal),
+    Binding(Identifier<'a>),
+    List(Vec<Pattern<'a>>),
+    /// The elements of a list that aren't matched by the other patterns of the list, such as `..rest`, which
+    /// can only appear once in a list pattern.
+    Rest(Option<Identifier<'a>>),
+    /// An enum, one of its variants and patterns for the variant's values.
+    Variant(Identifier<'a>, Identifier<'a>, Vec<Pattern<'a>>),
+    /// A struct and patterns for some of its fields.
+    Structure(Identifier<'a>, Vec<(Identifier<'a>, Pattern<'a>)>),
+    /// Literal keys and patterns for their values, which ignore the other keys of the map.
+    Map(Vec<(Literal, Pattern<'a>)>),
+}
+
+fn parse_literal(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    map(pattern_literal, Pattern::Literal).parse(input)
+}
+
+fn parse_binding(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    map(identifier, |identifier| match identifier {
+        "_" => Pattern::Wildcard,
+        identifier => Pattern::Binding(identifier),
+    })
+    .parse(input)
+}
+
+fn parse_rest(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    map(preceded(tag(".."), opt(identifier)), Pattern::Rest).parse(input)
+}
+
+fn parse_list(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    map(
+        delimited(
+            char('['),
+            verify(
+                separated_list0(char(','), trim(alt((parse_rest, pattern)))),
+                |patterns: &[Pattern]| {
+                    patterns
+                        .iter()
+                        .filter(|pattern| matches!(pattern, Pattern::Rest(_)))
+                        .count()
+                        <= 1
+                },
+            ),
+            char(']'),
+        ),
+        Pattern::List,
+    )
+    .parse(input)
+}
+
+/// Parses the pattern of a field, where `field` is short for `field: field`.
+fn parse_field(input: &str) -> IResult<&str, (Identifier<'_>, Pattern<'_>), SplashParseError<'_>> {
+    alt((
+        separated_pair(identifier, trim(char(':')), pattern),
+        map(identifier, |field| (field, Pattern::Binding(field))),
+    ))
+    .parse(input)
+}
+
+fn parse_structure(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            terminated(identifier, multispace0),
+            delimited(
+                char('{'),
+                separated_list0(char(','), trim(parse_field)),
+                char('}'),
+            ),
+        )),
+        |(structure, fields)| Pattern::Structure(structure, fields),
+    )
+    .parse(input)
+}
+
+fn parse_map(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    map(
+        delimited(
+            tag("#{"),
+            separated_list0(
+                char(','),
+                separated_pair(trim(pattern_literal), char(':'), trim(pattern)),
+            ),
+            char('}'),
+        ),
+        Pattern::Map,
+    )
+    .parse(input)
+}
+
+fn parse_variant(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            identifier,
+            preceded(char('.'), identifier),
+            opt(delimited(
+                char('('),
+                separated_list0(char(','), trim(pattern)),
+                char(')'),
+            )),
+        )),
+        |(enumeration, variant, values)| {
+            Pattern::Variant(enumeration, variant, values.unwrap_or_default())
+        },
+    )
+    .parse(input)
+}
+
+pub fn pattern(input: &str) -> IResult<&str, Pattern<'_>, SplashParseError<'_>> {
+    alt((
+        parse_literal,
+        parse_list,
+        parse_map,
+        parse_variant,
+        parse_structure,
+        parse_binding,
+    ))
+    .parse(input)
+}
@@ -6,6 +6,6 @@ use super::block::block;
 
 pub type Program<'a> = Block<'a>;
 
-pub fn program(input: &str) -> IResult<&str, Program, SplashParseError> {
+pub fn program(input: &str) -> IResult<&str, Program<'_>, SplashParseError<'_>> {
     all_consuming(block).parse(input)
 }
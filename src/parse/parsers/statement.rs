@@ -1,8 +1,9 @@
 use nom::{
     branch::alt,
-    character::complete::char,
-    combinator::{map, opt},
-    multi::separated_list0,
+    bytes::complete::{is_not, tag},
+    character::complete::{char, multispace0, space1},
+    combinator::{map, not, opt, verify},
+    multi::{many0, separated_list0, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
     IResult, Parser,
 };
@@ -11,112 +12,220 @@ use crate::parse::{combinators::trim::trim, SplashParseError};
 
 use super::{
     block::{block, Block},
-    expression::{expression, Expression},
+    expression::{accessors, expression, Accessor, Expression},
     identifier::{identifier, Identifier},
     keyword::{keyword, Keyword},
+    operator::{compound_operator, Operator},
+    parameter::{parameters, Parameter},
+    pattern::{pattern, Pattern},
 };
 
+/// An arm of a `match` statement: its body runs if the pattern matches and the optional guard holds.
+#[derive(Clone, Debug)]
+pub struct Arm<'a> {
+    pattern: Pattern<'a>,
+    guard: Option<Expression<'a>>,
+    body: Block<'a>,
+}
+
+impl<'a> Arm<'a> {
+    #[must_use]
+    pub fn pattern(&self) -> &Pattern<'a> {
+        &self.pattern
+    }
+
+    #[must_use]
+    pub fn guard(&self) -> Option<&Expression<'a>> {
+        self.guard.as_ref()
+    }
+
+    #[must_use]
+    pub fn body(&self) -> &Block<'a> {
+        &self.body
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Statement<'a> {
     Simple(Expression<'a>),
     Block(Block<'a>),
-    Initialization(Identifier<'a>, Expression<'a>),
+    Initialization(Pattern<'a>, Expression<'a>),
+    /// Declares a variable that cannot be assigned to afterwards.
+    Constant(Identifier<'a>, Expression<'a>),
     Assignment(Identifier<'a>, Expression<'a>),
-    IndexAssignment(Identifier<'a>, Expression<'a>, Expression<'a>),
-    If(Expression<'a>, Block<'a>),
-    IfElse(Expression<'a>, Block<'a>, Block<'a>),
-    While(Expression<'a>, Block<'a>),
-    For(Identifier<'a>, Expression<'a>, Block<'a>),
-    Definition(Identifier<'a>, Vec<Identifier<'a>>, Block<'a>),
+    IndexAssignment(Identifier<'a>, Vec<Accessor<'a>>, Expression<'a>),
+    /// Applies a binary operator to a variable and a value, such as `x += 1`.
+    CompoundAssignment(Identifier<'a>, Operator, Expression<'a>),
+    CompoundIndexAssignment(Identifier<'a>, Vec<Accessor<'a>>, Operator, Expression<'a>),
+    /// Assigns the variables bound by a list, struct or map pattern, such as `[a, b] = [b, a]`.
+    Destructuring(Pattern<'a>, Expression<'a>),
+    /// An `if` followed by any number of `else if`, each with its predicate and block, then an optional
+    /// `else` block.
+    If(Vec<(Expression<'a>, Block<'a>)>, Option<Block<'a>>),
+    While(Option<Identifier<'a>>, Expression<'a>, Block<'a>),
+    For(
+        Option<Identifier<'a>>,
+        Pattern<'a>,
+        Expression<'a>,
+        Block<'a>,
+    ),
+    Definition(Identifier<'a>, Vec<Parameter<'a>>, Block<'a>),
+    Structure(Identifier<'a>, Vec<Identifier<'a>>),
+    Enumeration(Identifier<'a>, Vec<(Identifier<'a>, Vec<Identifier<'a>>)>),
+    Match(Expression<'a>, Vec<Arm<'a>>),
+    /// A block, then an optional `catch` block with an optional binding for the error, then an optional
+    /// `finally` block.
+    Try(
+        Block<'a>,
+        Option<(Option<Identifier<'a>>, Block<'a>)>,
+        Option<Block<'a>>,
+    ),
+    Throw(Expression<'a>),
+    /// Imports a file as a namespace, named after the file unless an alias is given.
+    Import(&'a str, Option<Identifier<'a>>),
+    /// Imports some top-level variables or functions of a file.
+    ImportFrom(Vec<Identifier<'a>>, &'a str),
     Return(Option<Expression<'a>>),
+    Break(Option<Identifier<'a>>),
+    Continue(Option<Identifier<'a>>),
 }
 
-fn parse_simple(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_simple(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(trim(expression), Statement::Simple).parse(input)
 }
 
-fn parse_block(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_block(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(block, Statement::Block).parse(input)
 }
 
-fn parse_initialization(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_initialization(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         tuple((
-            delimited(keyword(Keyword::Let), trim(identifier), char('=')),
+            delimited(keyword(Keyword::Let), trim(pattern), char('=')),
             trim(expression),
         )),
-        |(identifier, expression)| Statement::Initialization(identifier, expression),
+        |(pattern, expression)| Statement::Initialization(pattern, expression),
     )
     .parse(input)
 }
 
-fn parse_assignment(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_constant(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
-        tuple((terminated(trim(identifier), char('=')), trim(expression))),
-        |(identifier, expression)| Statement::Assignment(identifier, expression),
+        tuple((
+            delimited(keyword(Keyword::Const), trim(identifier), char('=')),
+            trim(expression),
+        )),
+        |(identifier, expression)| Statement::Constant(identifier, expression),
     )
     .parse(input)
 }
 
-fn parse_index_assignment(input: &str) -> IResult<&str, Statement, SplashParseError> {
+/// Parses `=`, or the operator of a compound assignment.
+fn parse_assignment_operator(input: &str) -> IResult<&str, Option<Operator>, SplashParseError<'_>> {
+    alt((map(compound_operator, Some), map(char('='), |_| None))).parse(input)
+}
+
+fn parse_assignment(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         tuple((
             trim(identifier),
-            trim(delimited(char('['), trim(expression), char(']'))),
-            preceded(char('='), trim(expression)),
+            parse_assignment_operator,
+            trim(expression),
         )),
-        |(identifier, index, expression)| Statement::IndexAssignment(identifier, index, expression),
+        |(identifier, operator, expression)| match operator {
+            Some(operator) => Statement::CompoundAssignment(identifier, operator, expression),
+            None => Statement::Assignment(identifier, expression),
+        },
     )
     .parse(input)
 }
 
-fn parse_if(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_index_assignment(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         tuple((
-            preceded(keyword(Keyword::If), trim(expression)),
-            trim(block),
+            trim(identifier),
+            terminated(accessors, multispace0),
+            parse_assignment_operator,
+            trim(expression),
         )),
-        |(predicate, then)| Statement::If(predicate, then),
+        |(identifier, accessors, operator, expression)| match operator {
+            Some(operator) => {
+                Statement::CompoundIndexAssignment(identifier, accessors, operator, expression)
+            }
+            None => Statement::IndexAssignment(identifier, accessors, expression),
+        },
     )
     .parse(input)
 }
 
-fn parse_if_else(input: &str) -> IResult<&str, Statement, SplashParseError> {
+/// Plain variables are assigned by `parse_assignment`, so only patterns that destructure a value are parsed
+/// here. The `=` must not start a `==` comparison.
+fn parse_destructuring(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         tuple((
-            preceded(keyword(Keyword::If), trim(expression)),
-            trim(block),
-            preceded(keyword(Keyword::Else), trim(block)),
+            terminated(
+                trim(verify(pattern, |pattern| {
+                    matches!(
+                        pattern,
+                        Pattern::List(_) | Pattern::Structure(..) | Pattern::Map(_)
+                    )
+                })),
+                terminated(char('='), not(char('='))),
+            ),
+            trim(expression),
+        )),
+        |(pattern, expression)| Statement::Destructuring(pattern, expression),
+    )
+    .parse(input)
+}
+
+fn parse_if(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            separated_list1(
+                trim(keyword(Keyword::Else)),
+                tuple((
+                    preceded(keyword(Keyword::If), trim(expression)),
+                    trim(block),
+                )),
+            ),
+            opt(preceded(keyword(Keyword::Else), trim(block))),
         )),
-        |(predicate, then, otherwise)| Statement::IfElse(predicate, then, otherwise),
+        |(branches, otherwise)| Statement::If(branches, otherwise),
     )
     .parse(input)
 }
 
-fn parse_while(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_label(input: &str) -> IResult<&str, Option<Identifier<'_>>, SplashParseError<'_>> {
+    opt(terminated(trim(identifier), char(':'))).parse(input)
+}
+
+fn parse_while(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         tuple((
-            preceded(keyword(Keyword::While), trim(expression)),
+            parse_label,
+            preceded(trim(keyword(Keyword::While)), trim(expression)),
             trim(block),
         )),
-        |(predicate, body)| Statement::While(predicate, body),
+        |(label, predicate, body)| Statement::While(label, predicate, body),
     )
     .parse(input)
 }
 
-fn parse_for(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_for(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         tuple((
-            preceded(keyword(Keyword::For), trim(identifier)),
+            parse_label,
+            preceded(trim(keyword(Keyword::For)), trim(pattern)),
             preceded(keyword(Keyword::In), trim(expression)),
             trim(block),
         )),
-        |(identifier, list, block)| Statement::For(identifier, list, block),
+        |(label, pattern, list, block)| Statement::For(label, pattern, list, block),
     )
     .parse(input)
 }
 
-fn parse_return(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_return(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         preceded(keyword(Keyword::Return), opt(trim(expression))),
         Statement::Return,
@@ -124,34 +233,191 @@ fn parse_return(input: &str) -> IResult<&str, Statement, SplashParseError> {
     .parse(input)
 }
 
-fn parse_definition(input: &str) -> IResult<&str, Statement, SplashParseError> {
+// The label must be on the same line, so that it cannot be confused with the next statement.
+fn parse_break(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        preceded(keyword(Keyword::Break), opt(preceded(space1, identifier))),
+        Statement::Break,
+    )
+    .parse(input)
+}
+
+fn parse_continue(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        preceded(
+            keyword(Keyword::Continue),
+            opt(preceded(space1, identifier)),
+        ),
+        Statement::Continue,
+    )
+    .parse(input)
+}
+
+fn parse_definition(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     map(
         tuple((
             preceded(keyword(Keyword::Fn), trim(identifier)),
+            parameters,
+            trim(block),
+        )),
+        |(identifier, arguments, body)| Statement::Definition(identifier, arguments, body),
+    )
+    .parse(input)
+}
+
+fn parse_structure(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            preceded(keyword(Keyword::Struct), trim(identifier)),
             delimited(
-                char('('),
+                char('{'),
                 separated_list0(char(','), trim(identifier)),
-                char(')'),
+                preceded(multispace0, char('}')),
             ),
-            trim(block),
         )),
-        |(identifier, arguments, body)| Statement::Definition(identifier, arguments, body),
+        |(identifier, fields)| Statement::Structure(identifier, fields),
     )
     .parse(input)
 }
 
-pub fn statement(input: &str) -> IResult<&str, Statement, SplashParseError> {
+fn parse_enumeration(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            preceded(keyword(Keyword::Enum), trim(identifier)),
+            delimited(
+                char('{'),
+                separated_list0(
+                    char(','),
+                    trim(tuple((
+                        identifier,
+                        map(
+                            opt(delimited(
+                                char('('),
+                                separated_list0(char(','), trim(identifier)),
+                                char(')'),
+                            )),
+                            Option::unwrap_or_default,
+                        ),
+                    ))),
+                ),
+                char('}'),
+            ),
+        )),
+        |(identifier, variants)| Statement::Enumeration(identifier, variants),
+    )
+    .parse(input)
+}
+
+fn parse_arm(input: &str) -> IResult<&str, Arm<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            trim(pattern),
+            opt(preceded(keyword(Keyword::If), trim(expression))),
+            preceded(tag("=>"), trim(block)),
+        )),
+        |(pattern, guard, body)| Arm {
+            pattern,
+            guard,
+            body,
+        },
+    )
+    .parse(input)
+}
+
+fn parse_match(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            preceded(keyword(Keyword::Match), trim(expression)),
+            delimited(
+                char('{'),
+                many0(terminated(trim(parse_arm), opt(char(',')))),
+                char('}'),
+            ),
+        )),
+        |(expression, arms)| Statement::Match(expression, arms),
+    )
+    .parse(input)
+}
+
+fn parse_try(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        verify(
+            tuple((
+                preceded(keyword(Keyword::Try), trim(block)),
+                opt(preceded(
+                    keyword(Keyword::Catch),
+                    tuple((opt(trim(identifier)), trim(block))),
+                )),
+                opt(preceded(keyword(Keyword::Finally), trim(block))),
+            )),
+            |(_, catch, finally)| catch.is_some() || finally.is_some(),
+        ),
+        |(body, catch, finally)| Statement::Try(body, catch, finally),
+    )
+    .parse(input)
+}
+
+fn parse_throw(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        preceded(keyword(Keyword::Throw), trim(expression)),
+        Statement::Throw,
+    )
+    .parse(input)
+}
+
+/// Import paths are taken verbatim, without escape sequences.
+fn parse_path(input: &str) -> IResult<&str, &str, SplashParseError<'_>> {
+    delimited(char('"'), is_not("\""), char('"')).parse(input)
+}
+
+fn parse_import(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            preceded(keyword(Keyword::Import), trim(parse_path)),
+            opt(preceded(keyword(Keyword::As), trim(identifier))),
+        )),
+        |(path, alias)| Statement::Import(path, alias),
+    )
+    .parse(input)
+}
+
+fn parse_import_from(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
+    map(
+        tuple((
+            preceded(
+                keyword(Keyword::Import),
+                separated_list1(char(','), trim(identifier)),
+            ),
+            preceded(keyword(Keyword::From), trim(parse_path)),
+        )),
+        |(identifiers, path)| Statement::ImportFrom(identifiers, path),
+    )
+    .parse(input)
+}
+
+pub fn statement(input: &str) -> IResult<&str, Statement<'_>, SplashParseError<'_>> {
     terminated(
         alt((
             parse_return,
+            parse_break,
+            parse_continue,
             parse_definition,
+            parse_structure,
+            parse_enumeration,
+            parse_match,
+            parse_try,
+            parse_throw,
+            parse_import,
+            parse_import_from,
             parse_for,
             parse_while,
-            parse_if_else,
             parse_if,
+            // Declarations come before assignments, which would read `let [x] = ...` as indexing `let`.
+            parse_initialization,
+            parse_constant,
             parse_index_assignment,
             parse_assignment,
-            parse_initialization,
+            parse_destructuring,
             parse_block,
             parse_simple,
         )),
@@ -159,3 +425,33 @@ pub fn statement(input: &str) -> IResult<&str, Statement, SplashParseError> {
     )
     .parse(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::Pattern;
+
+    use super::{statement, Statement};
+
+    #[test]
+    fn single_element_destructuring() {
+        let (rest, parsed) = statement("let [x] = [1]").unwrap();
+
+        assert!(rest.is_empty());
+        assert!(matches!(
+            parsed,
+            Statement::Initialization(Pattern::List(patterns), _)
+                if matches!(patterns[..], [Pattern::Binding("x")])
+        ));
+    }
+
+    #[test]
+    fn index_assignment() {
+        let (rest, parsed) = statement("letters[0] = 1").unwrap();
+
+        assert!(rest.is_empty());
+        assert!(matches!(
+            parsed,
+            Statement::IndexAssignment("letters", _, _)
+        ));
+    }
+}
@@ -3,11 +3,13 @@
 //!
 //! - Enclosed by double quotes
 //! - Can contain any raw unescaped code point besides \ and "
-//! - Matches the following escape sequences: \b, \f, \n, \r, \t, \", \\, \/
+//! - Matches the following escape sequences: \b, \f, \n, \r, \t, \", \\, \/, \{, \}
 //! - Matches code points like Rust: \u{XXXX}, where XXXX can be up to 6
 //!   hex characters
 //! - an escape followed by whitespace consumes all whitespace between the
 //!   escape and the next non-whitespace character
+//! - Cannot contain unescaped braces, which are reserved for interpolation:
+//!   an interpolated string embeds expressions between braces, like "x = {x}"
 
 use nom::branch::alt;
 use nom::bytes::streaming::{is_not, take_while_m_n};
@@ -19,6 +21,10 @@ use nom::multi::fold_many0;
 use nom::sequence::{delimited, preceded};
 use nom::{IResult, Parser};
 
+use crate::parse::{combinators::trim::trim, SplashParseError};
+
+use super::expression::{expression, Expression};
+
 // parser combinators are constructed from the bottom up:
 // first we write parsers for the smallest elements (escaped characters),
 // then combine them into larger parsers.
@@ -81,6 +87,8 @@ where
             value('\\', char('\\')),
             value('/', char('/')),
             value('"', char('"')),
+            value('{', char('{')),
+            value('}', char('}')),
         )),
     )
     .parse(input)
@@ -95,14 +103,14 @@ where
     preceded(char('\\'), multispace1).parse(input)
 }
 
-/// Parse a non-empty block of text that doesn't include \ or "
+/// Parse a non-empty block of text that doesn't include \, ", { or }
 fn parse_literal<'a, E>(input: &'a str) -> IResult<&'a str, &'a str, E>
 where
     E: ParseError<&'a str>,
 {
     // `is_not` parses a string of 0 or more characters that aren't one of the
     // given characters.
-    let not_quote_slash = is_not("\"\\");
+    let not_quote_slash = is_not("\"\\{}");
 
     // `verify` runs a parser, then runs a verification function on the output of
     // the parser. The verification function accepts out output only if it
@@ -168,3 +176,58 @@ where
     // loop won't accidentally match your closing delimiter!
     delimited(char('"'), build_string, char('"')).parse(input)
 }
+
+/// A part of an interpolated string: either text, or an expression whose value is inserted in the text.
+#[derive(Clone, Debug)]
+pub enum StringPart<'a> {
+    Literal(String),
+    Expression(Expression<'a>),
+}
+
+/// A piece of an interpolated string being parsed: either a string fragment
+/// or an embedded expression.
+enum InterpolationPiece<'a> {
+    Fragment(StringFragment<'a>),
+    Expression(Expression<'a>),
+}
+
+/// Parse a string embedding expressions between braces. Consecutive
+/// fragments are joined into a single literal part.
+pub fn interpolated_string(
+    input: &str,
+) -> IResult<&str, Vec<StringPart<'_>>, SplashParseError<'_>> {
+    let build_parts = fold_many0(
+        alt((
+            map(parse_fragment, InterpolationPiece::Fragment),
+            map(
+                delimited(char('{'), trim(expression), char('}')),
+                InterpolationPiece::Expression,
+            ),
+        )),
+        Vec::new,
+        |mut parts, piece| {
+            let fragment = match piece {
+                InterpolationPiece::Fragment(fragment) => fragment,
+                InterpolationPiece::Expression(expression) => {
+                    parts.push(StringPart::Expression(expression));
+                    return parts;
+                }
+            };
+
+            if !matches!(parts.last(), Some(StringPart::Literal(_))) {
+                parts.push(StringPart::Literal(String::new()));
+            }
+
+            if let Some(StringPart::Literal(string)) = parts.last_mut() {
+                match fragment {
+                    StringFragment::Literal(s) => string.push_str(s),
+                    StringFragment::EscapedChar(c) => string.push(c),
+                    StringFragment::EscapedWS => {}
+                }
+            }
+            parts
+        },
+    );
+
+    delimited(char('"'), build_parts, char('"')).parse(input)
+}
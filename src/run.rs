@@ -1,10 +1,18 @@
 mod block;
 mod builtin;
+mod check;
 mod context;
+mod enumeration;
 mod error;
 mod evaluate;
 mod function;
+mod key;
+mod method;
+mod module;
+mod pattern;
+mod range;
 mod runtime;
+mod structure;
 mod utils;
 mod value;
 
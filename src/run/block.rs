@@ -1,20 +1,52 @@
-use crate::parse::{Block, Statement};
+use std::rc::Rc;
+
+use crate::parse::{Block, Identifier, Statement};
 
 use super::{
-    context::Context, evaluate::evaluate, utils::evaluate_predicate, value::Value,
+    builtin,
+    context::Context,
+    enumeration::Enumeration,
+    evaluate::{binary, evaluate},
+    module, pattern,
+    structure::Structure,
+    utils::{self, evaluate_predicate},
+    value::Value,
     SplashRuntimeError,
 };
 
 #[derive(Debug)]
-pub enum BlockValue {
-    Return(Option<Value>),
+pub enum BlockValue<'a> {
+    Return(Option<Value<'a>>),
+    Break(Option<Identifier<'a>>),
+    Continue(Option<Identifier<'a>>),
     None,
 }
 
+impl<'a> BlockValue<'a> {
+    /// Turns the value of a function body or of the program into its return value, failing if a `break` or a
+    /// `continue` reached it without meeting its loop.
+    pub fn returned(self) -> Result<Option<Value<'a>>, SplashRuntimeError<'a>> {
+        match self {
+            Self::Return(value) => Ok(value),
+            Self::None => Ok(None),
+            Self::Break(Some(label)) | Self::Continue(Some(label)) => {
+                Err(SplashRuntimeError::UndefinedLabel(label))
+            }
+            Self::Break(None) => Err(SplashRuntimeError::OutsideLoop("break")),
+            Self::Continue(None) => Err(SplashRuntimeError::OutsideLoop("continue")),
+        }
+    }
+}
+
+/// Whether a `break` or a `continue` with an optional `target` label applies to a loop labeled `label`.
+fn targets(label: &Option<Identifier>, target: &Option<Identifier>) -> bool {
+    target.is_none() || target == label
+}
+
 pub fn run<'a>(
     block: &Block<'a>,
     context: &mut Context<'a>,
-) -> Result<BlockValue, SplashRuntimeError<'a>> {
+) -> Result<BlockValue<'a>, SplashRuntimeError<'a>> {
     for statement in block.statements() {
         match statement {
             Statement::Simple(expression) => {
@@ -24,86 +56,109 @@ pub fn run<'a>(
                 let value = context.child(|context| self::run(block, context))?;
 
                 match value {
-                    BlockValue::Return(value) => return Ok(BlockValue::Return(value)),
                     BlockValue::None => {}
+                    value => return Ok(value),
                 }
             }
-            Statement::Initialization(identifier, expression) => {
+            Statement::Initialization(pattern, expression) => {
                 let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
-                context.initialize_variable(identifier, value);
+                for (identifier, value) in pattern::destructure(pattern, value, context)? {
+                    context.initialize_variable(identifier, value);
+                }
             }
             Statement::Assignment(identifier, expression) => {
                 let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
                 context.assign_variable(identifier, value)?;
             }
-            Statement::IndexAssignment(identifier, index, expression) => {
-                let list = context.variable(identifier)?;
-                let index = evaluate(index, context)?.ok_or(SplashRuntimeError::NoValue)?;
-
-                let mut list = match list {
-                    Value::List(list) => list,
-                    value => return Err(SplashRuntimeError::NotAList(value)),
-                };
-
-                let index = match index {
-                    Value::Number(number) => number as usize,
-                    value => return Err(SplashRuntimeError::NotAnIndex(value)),
-                };
-
+            Statement::IndexAssignment(identifier, accessors, expression) => {
+                let mut collection = context.variable(identifier)?;
+                let accesses = utils::evaluate_accessors(accessors, context)?;
                 let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
 
-                if index < list.len() as usize {
-                    list[index as usize] = value;
-                    context.assign_variable(identifier, Value::List(list))?
-                } else {
-                    return Err(SplashRuntimeError::OutOufRange(Value::Number(index as f64)));
-                }
+                utils::set_nested(&mut collection, accesses, value)?;
+                context.assign_variable(identifier, collection)?;
             }
-            Statement::If(predicate, then) => {
-                if evaluate_predicate(predicate, context)? {
-                    match context.child(|context| self::run(then, context))? {
-                        BlockValue::Return(value) => return Ok(BlockValue::Return(value)),
-                        BlockValue::None => {}
-                    }
+            Statement::Constant(identifier, expression) => {
+                let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+                context.initialize_constant(identifier, value);
+            }
+            Statement::Destructuring(pattern, expression) => {
+                let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+                for (identifier, value) in pattern::destructure(pattern, value, context)? {
+                    context.assign_variable(identifier, value)?;
                 }
             }
-            Statement::IfElse(predicate, then, otherwise) => {
-                if evaluate_predicate(predicate, context)? {
-                    match context.child(|context| self::run(then, context))? {
-                        BlockValue::Return(value) => return Ok(BlockValue::Return(value)),
-                        BlockValue::None => {}
+            Statement::CompoundAssignment(identifier, operator, expression) => {
+                let current = context.variable(identifier)?;
+                let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+                let value = binary(operator, current, value)?.ok_or(SplashRuntimeError::NoValue)?;
+                context.assign_variable(identifier, value)?;
+            }
+            Statement::CompoundIndexAssignment(identifier, accessors, operator, expression) => {
+                let mut collection = context.variable(identifier)?;
+                let accesses = utils::evaluate_accessors(accessors, context)?;
+                let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+
+                utils::update_nested(&mut collection, accesses, |current| {
+                    binary(operator, current, value)?.ok_or(SplashRuntimeError::NoValue)
+                })?;
+                context.assign_variable(identifier, collection)?;
+            }
+            Statement::If(branches, otherwise) => {
+                let mut chosen = otherwise.as_ref();
+                for (predicate, then) in branches {
+                    if evaluate_predicate(predicate, context)? {
+                        chosen = Some(then);
+                        break;
                     }
-                } else {
-                    match context.child(|context| self::run(otherwise, context))? {
-                        BlockValue::Return(value) => return Ok(BlockValue::Return(value)),
+                }
+
+                if let Some(chosen) = chosen {
+                    match context.child(|context| self::run(chosen, context))? {
                         BlockValue::None => {}
+                        value => return Ok(value),
                     }
                 }
             }
-            Statement::While(predicate, body) => {
+            Statement::While(label, predicate, body) => {
                 while evaluate_predicate(predicate, context)? {
                     match context.child(|context| self::run(body, context))? {
-                        BlockValue::Return(value) => return Ok(BlockValue::Return(value)),
                         BlockValue::None => {}
+                        BlockValue::Break(target) if targets(label, &target) => break,
+                        BlockValue::Continue(target) if targets(label, &target) => continue,
+                        value => return Ok(value),
                     }
                 }
             }
-            Statement::For(identifier, list, body) => {
-                let list = evaluate(list, context)?.ok_or(SplashRuntimeError::NoValue)?;
-                match list {
-                    Value::List(list) => {
-                        for element in list {
-                            let block_value = context.child(|context| {
-                                context.initialize_variable(identifier, element);
-                                self::run(body, context)
-                            })?;
-                            match block_value {
-                                BlockValue::Return(value) => return Ok(BlockValue::Return(value)),
-                                BlockValue::None => {}
-                            }
+            Statement::For(label, pattern, iterable, body) => {
+                // Ranges are iterated lazily, without building the list of their elements.
+                let elements: Box<dyn Iterator<Item = Value>> =
+                    match evaluate(iterable, context)?.ok_or(SplashRuntimeError::NoValue)? {
+                        Value::List(list) => Box::new(list.into_iter()),
+                        Value::Map(map) => Box::new(map.into_keys().map(Value::from)),
+                        Value::Range(range) => Box::new(range.iter().map(Value::Integer)),
+                        Value::String(string) => Box::new(
+                            builtin::string::characters(&string)
+                                .collect::<Vec<_>>()
+                                .into_iter(),
+                        ),
+                        value => return Err(SplashRuntimeError::NotAList(value)),
+                    };
+
+                for element in elements {
+                    let block_value = context.child(|context| {
+                        for (identifier, value) in pattern::destructure(pattern, element, context)?
+                        {
+                            context.initialize_variable(identifier, value);
                         }
+                        self::run(body, context)
+                    })?;
+                    match block_value {
+                        BlockValue::None => {}
+                        BlockValue::Break(target) if targets(label, &target) => break,
+                        BlockValue::Continue(target) if targets(label, &target) => continue,
+                        value => return Ok(value),
                     }
-                    value => return Err(SplashRuntimeError::NotAList(value)),
                 }
             }
             Statement::Return(expression) => {
@@ -112,9 +167,98 @@ pub fn run<'a>(
                     None => Ok(BlockValue::Return(None)),
                 };
             }
+            Statement::Break(label) => return Ok(BlockValue::Break(*label)),
+            Statement::Continue(label) => return Ok(BlockValue::Continue(*label)),
             Statement::Definition(identifier, arguments, body) => {
                 context.initialize_function(identifier, arguments.clone(), body.clone());
             }
+            Statement::Enumeration(identifier, variants) => {
+                let enumeration = Enumeration::new(identifier, variants.clone());
+                context.initialize_variable(identifier, Value::Enumeration(Rc::new(enumeration)));
+            }
+            Statement::Match(expression, arms) => {
+                let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+                let mut matched = false;
+
+                for arm in arms {
+                    let mut bindings = Vec::new();
+                    if !pattern::matches(arm.pattern(), &value, context, &mut bindings)? {
+                        continue;
+                    }
+
+                    // The guard sees the variables bound by the pattern, so it is evaluated in the arm's scope.
+                    let block_value = context.child(|context| {
+                        for (identifier, value) in bindings {
+                            context.initialize_variable(identifier, value);
+                        }
+
+                        if let Some(guard) = arm.guard() {
+                            if !evaluate_predicate(guard, context)? {
+                                return Ok(None);
+                            }
+                        }
+
+                        self::run(arm.body(), context).map(Some)
+                    })?;
+
+                    match block_value {
+                        None => continue,
+                        Some(BlockValue::None) => {}
+                        Some(value) => return Ok(value),
+                    }
+
+                    matched = true;
+                    break;
+                }
+
+                if !matched {
+                    return Err(SplashRuntimeError::NoMatch(value));
+                }
+            }
+            Statement::Try(body, catch, finally) => {
+                let result = match (context.child(|context| self::run(body, context)), catch) {
+                    (Err(error), Some((identifier, handler))) => context.child(|context| {
+                        if let Some(identifier) = identifier {
+                            context.initialize_variable(identifier, error.into_value());
+                        }
+
+                        self::run(handler, context)
+                    }),
+                    (result, _) => result,
+                };
+
+                // Unless the `finally` block itself exits, the outcome of the `try` or `catch` block stands.
+                if let Some(finally) = finally {
+                    match context.child(|context| self::run(finally, context))? {
+                        BlockValue::None => {}
+                        value => return Ok(value),
+                    }
+                }
+
+                match result? {
+                    BlockValue::None => {}
+                    value => return Ok(value),
+                }
+            }
+            Statement::Throw(expression) => {
+                let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+                return Err(SplashRuntimeError::Thrown(value));
+            }
+            Statement::Import(path, alias) => {
+                let module = module::import(path, context)?;
+                let name = alias.unwrap_or(module.name());
+                context.initialize_variable(name, Value::Module(module));
+            }
+            Statement::ImportFrom(identifiers, path) => {
+                let module = module::import(path, context)?;
+                for identifier in identifiers {
+                    context.initialize_variable(identifier, module.member(identifier)?);
+                }
+            }
+            Statement::Structure(identifier, fields) => {
+                let structure = Structure::new(identifier, fields.clone());
+                context.initialize_variable(identifier, Value::Structure(Rc::new(structure)));
+            }
         }
     }
 
@@ -1,146 +1,306 @@
-use super::{evaluate::Result, value::Value, SplashRuntimeError};
+pub mod higher_order;
+pub mod list;
+pub mod math;
+pub mod string;
+
+use std::cmp::Ordering;
+
+use super::{
+    evaluate::Result, function::Arity, key::Key, range::Range, value::Value, SplashRuntimeError,
+};
 
 /*************
  * OPERATORS *
  *************/
 
-pub fn not(value: Value) -> Result<'static> {
+pub fn not<'a>(value: Value<'a>) -> Result<'a> {
     match value {
         Value::Boolean(boolean) => Ok(Some(Value::Boolean(!boolean))),
         value => Err(SplashRuntimeError::InvalidSignatureType("not", vec![value])),
     }
 }
 
-pub fn plus(left: Value, right: Value) -> Result<'static> {
+pub fn negate<'a>(value: Value<'a>) -> Result<'a> {
+    match value {
+        Value::Integer(value) => integer("negate", value.checked_neg()),
+        Value::Number(number) => Ok(Some(Value::Number(-number))),
+        value => Err(SplashRuntimeError::InvalidSignatureType(
+            "negate",
+            vec![value],
+        )),
+    }
+}
+
+/// Converts both operands to floats if they are numbers and at least one of them is a float. Operations between
+/// two integers stay exact instead.
+fn floats(left: &Value, right: &Value) -> Option<(f64, f64)> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => Ok(Some(Value::Number(left + right))),
+        (Value::Number(left), Value::Number(right)) => Some((*left, *right)),
+        (Value::Integer(left), Value::Number(right)) => Some((*left as f64, *right)),
+        (Value::Number(left), Value::Integer(right)) => Some((*left, *right as f64)),
+        _ => None,
+    }
+}
+
+fn integer<'a>(name: &'a str, integer: Option<i64>) -> Result<'a> {
+    match integer {
+        Some(integer) => Ok(Some(Value::Integer(integer))),
+        None => Err(SplashRuntimeError::Overflow(name)),
+    }
+}
+
+pub fn plus<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    match (left, right) {
+        (Value::Integer(left), Value::Integer(right)) => integer("plus", left.checked_add(right)),
         (Value::String(left), Value::String(right)) => {
             let mut result = left;
             result.push_str(&right);
             Ok(Some(Value::String(result)))
         }
-        (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "plus",
-            vec![left, right],
-        )),
+        (left, right) => match floats(&left, &right) {
+            Some((left, right)) => Ok(Some(Value::Number(left + right))),
+            None => Err(SplashRuntimeError::InvalidSignatureType(
+                "plus",
+                vec![left, right],
+            )),
+        },
     }
 }
 
-pub fn minus(left: Value, right: Value) -> Result<'static> {
+pub fn minus<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => Ok(Some(Value::Number(left - right))),
-        (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "minus",
-            vec![left, right],
-        )),
+        (Value::Integer(left), Value::Integer(right)) => integer("minus", left.checked_sub(right)),
+        (left, right) => match floats(&left, &right) {
+            Some((left, right)) => Ok(Some(Value::Number(left - right))),
+            None => Err(SplashRuntimeError::InvalidSignatureType(
+                "minus",
+                vec![left, right],
+            )),
+        },
     }
 }
 
-pub fn times(left: Value, right: Value) -> Result<'static> {
+pub fn times<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => Ok(Some(Value::Number(left * right))),
-        (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "times",
-            vec![left, right],
-        )),
+        (Value::Integer(left), Value::Integer(right)) => integer("times", left.checked_mul(right)),
+        (left, right) => match floats(&left, &right) {
+            Some((left, right)) => Ok(Some(Value::Number(left * right))),
+            None => Err(SplashRuntimeError::InvalidSignatureType(
+                "times",
+                vec![left, right],
+            )),
+        },
     }
 }
 
-pub fn divide(left: Value, right: Value) -> Result<'static> {
+/// Raising an integer to a non-negative integer power stays exact, while negative powers give a float.
+pub fn power<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => {
-            if right == 0.0 {
-                Err(SplashRuntimeError::DivisionByZero)
-            } else {
-                Ok(Some(Value::Number(left / right)))
-            }
+        (Value::Integer(left), Value::Integer(right)) if right >= 0 => integer(
+            "power",
+            u32::try_from(right)
+                .ok()
+                .and_then(|right| left.checked_pow(right)),
+        ),
+        (Value::Integer(left), Value::Integer(right)) => {
+            Ok(Some(Value::Number((left as f64).powf(right as f64))))
         }
+        (left, right) => match floats(&left, &right) {
+            Some((left, right)) => Ok(Some(Value::Number(left.powf(right)))),
+            None => Err(SplashRuntimeError::InvalidSignatureType(
+                "power",
+                vec![left, right],
+            )),
+        },
+    }
+}
+
+/// Dividing two integers truncates the result toward zero.
+pub fn divide<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    match (left, right) {
+        (Value::Integer(_), Value::Integer(0)) => Err(SplashRuntimeError::DivisionByZero),
+        (Value::Integer(left), Value::Integer(right)) => integer("divide", left.checked_div(right)),
+        (left, right) => match floats(&left, &right) {
+            Some((_, 0.0)) => Err(SplashRuntimeError::DivisionByZero),
+            Some((left, right)) => Ok(Some(Value::Number(left / right))),
+            None => Err(SplashRuntimeError::InvalidSignatureType(
+                "divide",
+                vec![left, right],
+            )),
+        },
+    }
+}
+
+pub fn modulo<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    match (left, right) {
+        (Value::Integer(_), Value::Integer(0)) => Err(SplashRuntimeError::DivisionByZero),
+        (Value::Integer(left), Value::Integer(right)) => integer("modulo", left.checked_rem(right)),
+        (left, right) => match floats(&left, &right) {
+            Some((_, 0.0)) => Err(SplashRuntimeError::DivisionByZero),
+            Some((left, right)) => Ok(Some(Value::Number(left % right))),
+            None => Err(SplashRuntimeError::InvalidSignatureType(
+                "modulo",
+                vec![left, right],
+            )),
+        },
+    }
+}
+
+pub fn equal<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    Ok(Some(Value::Boolean(left == right)))
+}
+
+pub fn not_equal<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    Ok(Some(Value::Boolean(left != right)))
+}
+
+/// Compares two numbers, which is `None` if one of them is NaN.
+fn compare<'a>(
+    name: &'a str,
+    left: Value<'a>,
+    right: Value<'a>,
+) -> std::result::Result<Option<Ordering>, SplashRuntimeError<'a>> {
+    match (left, right) {
+        (Value::Integer(left), Value::Integer(right)) => Ok(Some(left.cmp(&right))),
+        (left, right) => match floats(&left, &right) {
+            Some((left, right)) => Ok(left.partial_cmp(&right)),
+            None => Err(SplashRuntimeError::InvalidSignatureType(
+                name,
+                vec![left, right],
+            )),
+        },
+    }
+}
+
+pub fn greater_or_equal<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    let ordering = compare("greater_or_equal", left, right)?;
+    Ok(Some(Value::Boolean(ordering.is_some_and(Ordering::is_ge))))
+}
+
+pub fn greater_than<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    let ordering = compare("greater_than", left, right)?;
+    Ok(Some(Value::Boolean(ordering.is_some_and(Ordering::is_gt))))
+}
+
+pub fn less_or_equal<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    let ordering = compare("less_or_equal", left, right)?;
+    Ok(Some(Value::Boolean(ordering.is_some_and(Ordering::is_le))))
+}
+
+pub fn less_than<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    let ordering = compare("less_than", left, right)?;
+    Ok(Some(Value::Boolean(ordering.is_some_and(Ordering::is_lt))))
+}
+
+pub fn and<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    match (left, right) {
+        (Value::Boolean(left), Value::Boolean(right)) => Ok(Some(Value::Boolean(left && right))),
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "divide",
+            "and",
             vec![left, right],
         )),
     }
 }
 
-pub fn modulo(left: Value, right: Value) -> Result<'static> {
+pub fn or<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => {
-            if right == 0.0 {
-                Err(SplashRuntimeError::DivisionByZero)
-            } else {
-                Ok(Some(Value::Number(left % right)))
-            }
-        }
+        (Value::Boolean(left), Value::Boolean(right)) => Ok(Some(Value::Boolean(left || right))),
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "modulo",
+            "or",
             vec![left, right],
         )),
     }
 }
 
-pub fn equal(left: Value, right: Value) -> Result<'static> {
-    Ok(Some(Value::Boolean(left == right)))
-}
-
-pub fn not_equal(left: Value, right: Value) -> Result<'static> {
-    Ok(Some(Value::Boolean(left != right)))
+pub fn bit_and<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    match (left, right) {
+        (Value::Integer(left), Value::Integer(right)) => Ok(Some(Value::Integer(left & right))),
+        (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
+            "bit_and",
+            vec![left, right],
+        )),
+    }
 }
 
-pub fn greater_or_equal(left: Value, right: Value) -> Result<'static> {
+pub fn bit_or<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => Ok(Some(Value::Boolean(left >= right))),
+        (Value::Integer(left), Value::Integer(right)) => Ok(Some(Value::Integer(left | right))),
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "greater_or_equal",
+            "bit_or",
             vec![left, right],
         )),
     }
 }
 
-pub fn greater_than(left: Value, right: Value) -> Result<'static> {
+pub fn bit_xor<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => Ok(Some(Value::Boolean(left > right))),
+        (Value::Integer(left), Value::Integer(right)) => Ok(Some(Value::Integer(left ^ right))),
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "greater_than",
+            "bit_xor",
             vec![left, right],
         )),
     }
 }
 
-pub fn less_or_equal(left: Value, right: Value) -> Result<'static> {
+/// Shifting by a negative amount or by 64 bits or more overflows.
+pub fn shift_left<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => Ok(Some(Value::Boolean(left <= right))),
+        (Value::Integer(left), Value::Integer(right)) => integer(
+            "shift_left",
+            u32::try_from(right)
+                .ok()
+                .and_then(|right| left.checked_shl(right)),
+        ),
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "less_or_equal",
+            "shift_left",
             vec![left, right],
         )),
     }
 }
 
-pub fn less_than(left: Value, right: Value) -> Result<'static> {
+/// Shifting right is arithmetic: the sign of the integer is kept.
+pub fn shift_right<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Number(left), Value::Number(right)) => Ok(Some(Value::Boolean(left < right))),
+        (Value::Integer(left), Value::Integer(right)) => integer(
+            "shift_right",
+            u32::try_from(right)
+                .ok()
+                .and_then(|right| left.checked_shr(right)),
+        ),
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "less_than",
+            "shift_right",
             vec![left, right],
         )),
     }
 }
 
-pub fn and(left: Value, right: Value) -> Result<'static> {
+/// Returns `left`, unless it is nil.
+pub fn coalesce<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    match left {
+        Value::Nil => Ok(Some(right)),
+        left => Ok(Some(left)),
+    }
+}
+
+pub fn exclusive_range<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Boolean(left), Value::Boolean(right)) => Ok(Some(Value::Boolean(left && right))),
+        (Value::Integer(left), Value::Integer(right)) => {
+            Ok(Some(Value::Range(Range::new(left, right, 1)?)))
+        }
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "and",
+            "exclusive_range",
             vec![left, right],
         )),
     }
 }
 
-pub fn or(left: Value, right: Value) -> Result<'static> {
+pub fn inclusive_range<'a>(left: Value<'a>, right: Value<'a>) -> Result<'a> {
     match (left, right) {
-        (Value::Boolean(left), Value::Boolean(right)) => Ok(Some(Value::Boolean(left || right))),
+        (Value::Integer(left), Value::Integer(right)) => {
+            Ok(Some(Value::Range(Range::inclusive(left, right)?)))
+        }
         (left, right) => Err(SplashRuntimeError::InvalidSignatureType(
-            "or",
+            "inclusive_range",
             vec![left, right],
         )),
     }
@@ -150,104 +310,207 @@ pub fn or(left: Value, right: Value) -> Result<'static> {
  * FUNCTIONS *
  *************/
 
-pub fn print(arguments: &[Value]) -> Result<'static> {
-    if arguments.len() != 1 {
-        return Err(SplashRuntimeError::InvalidSignature(
-            "print",
-            1,
+fn expect_arity<'a>(
+    name: &'a str,
+    arguments: &[Value<'a>],
+    arity: Arity,
+) -> std::result::Result<(), SplashRuntimeError<'a>> {
+    if arity.accepts(arguments.len()) {
+        Ok(())
+    } else {
+        Err(SplashRuntimeError::InvalidSignature(
+            name,
+            arity,
             arguments.len(),
-        ));
+        ))
     }
+}
+
+pub fn print<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("print", arguments, Arity::exactly(1))?;
 
     println!("{}", arguments[0]);
     Ok(None)
 }
 
-pub fn string(arguments: &[Value]) -> Result<'static> {
-    if arguments.len() != 1 {
-        return Err(SplashRuntimeError::InvalidSignature(
-            "string",
-            1,
-            arguments.len(),
-        ));
-    }
+pub fn string<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("string", arguments, Arity::exactly(1))?;
 
     Ok(Some(Value::String(format!("{}", arguments[0]))))
 }
 
-pub fn length(arguments: &[Value]) -> Result<'static> {
-    if arguments.len() != 1 {
-        return Err(SplashRuntimeError::InvalidSignature(
-            "length",
-            1,
-            arguments.len(),
-        ));
-    }
+pub fn length<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("length", arguments, Arity::exactly(1))?;
 
     let len = match &arguments[0] {
         Value::List(list) => list.len(),
-        Value::String(string) => string.len(),
+        Value::String(string) => string.chars().count(),
+        Value::Map(map) => map.len(),
+        Value::Range(range) => range.len(),
         value => return Err(SplashRuntimeError::NotAList(value.clone())),
     };
 
-    Ok(Some(Value::Number(len as f64)))
+    // Ranges spanning most integers have more elements than an integer can count.
+    let len = i64::try_from(len).map_err(|_| SplashRuntimeError::Overflow("length"))?;
+    Ok(Some(Value::Integer(len)))
 }
 
-pub fn push(arguments: &[Value]) -> Result<'static> {
-    if arguments.len() != 2 {
-        return Err(SplashRuntimeError::InvalidSignature(
-            "push",
-            2,
-            arguments.len(),
-        ));
-    }
+pub fn push<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("push", arguments, Arity::exactly(2))?;
 
-    let mut list = match &arguments[0] {
-        Value::List(list) => list.clone(),
-        value => return Err(SplashRuntimeError::NotAList(value.clone())),
-    };
+    let mut list = list::elements(arguments, 0)?;
 
     list.push(arguments[1].clone());
     Ok(Some(Value::List(list)))
 }
 
-pub fn pop(arguments: &[Value]) -> Result<'static> {
-    if arguments.len() != 1 {
-        return Err(SplashRuntimeError::InvalidSignature(
-            "pop",
-            1,
-            arguments.len(),
-        ));
-    }
+pub fn pop<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("pop", arguments, Arity::exactly(1))?;
 
-    let mut list = match &arguments[0] {
-        Value::List(list) => list.clone(),
-        value => return Err(SplashRuntimeError::NotAList(value.clone())),
-    };
+    let mut list = list::elements(arguments, 0)?;
 
     list.pop();
     Ok(Some(Value::List(list)))
 }
 
-pub fn range(arguments: &[Value]) -> Result<'static> {
-    if arguments.len() != 1 {
-        return Err(SplashRuntimeError::InvalidSignature(
-            "range",
-            1,
-            arguments.len(),
-        ));
-    }
-
-    let size = match &arguments[0] {
-        Value::Number(number) => *number as usize,
-        _ => {
-            return Err(SplashRuntimeError::InvalidSignatureType(
+/// `range(stop)` counts from zero, `range(start, stop)` by steps of one, and `range(start, stop, step)` by
+/// any non-zero step. The result is a range rather than a list, which can be indexed, passed to list functions
+/// and compared with lists, but must be converted with `list` before being modified in place.
+pub fn range<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("range", arguments, Arity::between(1, 3))?;
+
+    let bounds = arguments
+        .iter()
+        .map(|argument| match argument {
+            Value::Integer(integer) => Ok(*integer),
+            _ => Err(SplashRuntimeError::InvalidSignatureType(
                 "range",
                 arguments.to_vec(),
-            ))
-        }
+            )),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let range = match bounds[..] {
+        [stop] => Range::new(0, stop, 1),
+        [start, stop] => Range::new(start, stop, 1),
+        [start, stop, step] => Range::new(start, stop, step),
+        _ => unreachable!("there are between one and three arguments"),
+    }?;
+
+    Ok(Some(Value::Range(range)))
+}
+
+/// Applies a predicate to the single number given to the function `name`, integers being tested separately
+/// from floats.
+fn number_predicate<'a>(
+    name: &'a str,
+    arguments: &[Value<'a>],
+    integer: fn(i64) -> bool,
+    number: fn(f64) -> bool,
+) -> Result<'a> {
+    expect_arity(name, arguments, Arity::exactly(1))?;
+
+    match arguments[0] {
+        Value::Integer(value) => Ok(Some(Value::Boolean(integer(value)))),
+        Value::Number(value) => Ok(Some(Value::Boolean(number(value)))),
+        _ => Err(SplashRuntimeError::InvalidSignatureType(
+            name,
+            arguments.to_vec(),
+        )),
+    }
+}
+
+pub fn is_nan<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    number_predicate("is_nan", arguments, |_| false, f64::is_nan)
+}
+
+/// Whether a number is neither infinite nor NaN.
+pub fn is_finite<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    number_predicate("is_finite", arguments, |_| true, f64::is_finite)
+}
+
+/// Whether a number is an integer or a float without a fractional part.
+pub fn is_integer<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    number_predicate(
+        "is_integer",
+        arguments,
+        |_| true,
+        |number| number.is_finite() && number.fract() == 0.0,
+    )
+}
+
+/// Collects the elements of a range into a list.
+pub fn list<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("list", arguments, Arity::exactly(1))?;
+
+    match &arguments[0] {
+        Value::List(list) => Ok(Some(Value::List(list.clone()))),
+        Value::Range(range) => Ok(Some(Value::List(
+            range.iter().map(Value::Integer).collect(),
+        ))),
+        value => Err(SplashRuntimeError::NotAList(value.clone())),
+    }
+}
+
+pub fn keys<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("keys", arguments, Arity::exactly(1))?;
+
+    let map = match &arguments[0] {
+        Value::Map(map) => map,
+        value => return Err(SplashRuntimeError::NotAMap(value.clone())),
+    };
+
+    Ok(Some(Value::List(
+        map.keys().cloned().map(Value::from).collect(),
+    )))
+}
+
+pub fn values<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("values", arguments, Arity::exactly(1))?;
+
+    let map = match &arguments[0] {
+        Value::Map(map) => map,
+        value => return Err(SplashRuntimeError::NotAMap(value.clone())),
+    };
+
+    Ok(Some(Value::List(map.values().cloned().collect())))
+}
+
+pub fn entries<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("entries", arguments, Arity::exactly(1))?;
+
+    let map = match &arguments[0] {
+        Value::Map(map) => map,
+        value => return Err(SplashRuntimeError::NotAMap(value.clone())),
+    };
+
+    Ok(Some(Value::List(
+        map.iter()
+            .map(|(key, value)| Value::List(vec![Value::from(key.clone()), value.clone()]))
+            .collect(),
+    )))
+}
+
+pub fn has<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("has", arguments, Arity::exactly(2))?;
+
+    let map = match &arguments[0] {
+        Value::Map(map) => map,
+        value => return Err(SplashRuntimeError::NotAMap(value.clone())),
+    };
+
+    let key = Key::try_from(arguments[1].clone())?;
+    Ok(Some(Value::Boolean(map.contains_key(&key))))
+}
+
+pub fn remove<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("remove", arguments, Arity::exactly(2))?;
+
+    let mut map = match &arguments[0] {
+        Value::Map(map) => map.clone(),
+        value => return Err(SplashRuntimeError::NotAMap(value.clone())),
     };
 
-    let range = (0..size).map(|i| Value::Number(i as f64)).collect();
-    Ok(Some(Value::List(range)))
+    map.remove(&Key::try_from(arguments[1].clone())?);
+    Ok(Some(Value::Map(map)))
 }
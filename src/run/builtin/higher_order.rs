@@ -0,0 +1,217 @@
+use std::{cmp::Ordering, collections::BTreeMap, rc::Rc};
+
+use crate::run::{
+    context::Context,
+    evaluate::{call, Result},
+    function::{Arity, Function},
+    key::Key,
+    value::Value,
+    SplashRuntimeError,
+};
+
+use super::{
+    expect_arity,
+    list::{elements, order},
+    string,
+};
+
+/// The function given at `position` among the arguments.
+fn function<'a>(
+    arguments: &[Value<'a>],
+    position: usize,
+) -> std::result::Result<Rc<Function<'a>>, SplashRuntimeError<'a>> {
+    match &arguments[position] {
+        Value::Function(function) => Ok(Rc::clone(function)),
+        value => Err(SplashRuntimeError::NotAFunction(value.clone())),
+    }
+}
+
+/// Calls a function given to a built-in, named after its `role` in errors about its arguments. Errors raised
+/// by the function are returned unchanged.
+fn apply<'a>(
+    role: &'a str,
+    function: &Function<'a>,
+    arguments: Vec<Value<'a>>,
+    context: &mut Context<'a>,
+) -> std::result::Result<Value<'a>, SplashRuntimeError<'a>> {
+    call(role, function, arguments, Vec::new(), context)?.ok_or(SplashRuntimeError::NoValue)
+}
+
+/// Calls a predicate given to a built-in, which must return a boolean.
+fn test<'a>(
+    predicate: &Function<'a>,
+    element: Value<'a>,
+    context: &mut Context<'a>,
+) -> std::result::Result<bool, SplashRuntimeError<'a>> {
+    match apply("predicate", predicate, vec![element], context)? {
+        Value::Boolean(boolean) => Ok(boolean),
+        value => Err(SplashRuntimeError::InvalidPredicate(value)),
+    }
+}
+
+pub fn map<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    expect_arity("map", arguments, Arity::exactly(2))?;
+    let (list, function) = (elements(arguments, 0)?, function(arguments, 1)?);
+
+    let list = list
+        .into_iter()
+        .map(|element| apply("callback", &function, vec![element], context))
+        .collect::<std::result::Result<_, _>>()?;
+
+    Ok(Some(Value::List(list)))
+}
+
+/// The elements for which a predicate returns `true`.
+pub fn filter<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    expect_arity("filter", arguments, Arity::exactly(2))?;
+    let (list, predicate) = (elements(arguments, 0)?, function(arguments, 1)?);
+
+    let mut kept = Vec::new();
+    for element in list {
+        if test(&predicate, element.clone(), context)? {
+            kept.push(element);
+        }
+    }
+
+    Ok(Some(Value::List(kept)))
+}
+
+/// Combines the elements from left to right with a function of the accumulated value and the next element,
+/// called as `reduce(list, function, initial)`. Without an initial value, the first element is used.
+pub fn reduce<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    expect_arity("reduce", arguments, Arity::between(2, 3))?;
+    let (list, function) = (elements(arguments, 0)?, function(arguments, 1)?);
+
+    let mut list = list.into_iter();
+    let initial = match arguments.get(2) {
+        Some(initial) => initial.clone(),
+        None => list.next().ok_or_else(|| {
+            SplashRuntimeError::InvalidSignatureType("reduce", arguments.to_vec())
+        })?,
+    };
+
+    list.try_fold(initial, |accumulator, element| {
+        apply("callback", &function, vec![accumulator, element], context)
+    })
+    .map(Some)
+}
+
+/// Sorts the elements by the numbers or strings a function returns for them, keeping elements with equal keys
+/// in their original order.
+pub fn sort_by<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    expect_arity("sort_by", arguments, Arity::exactly(2))?;
+    let (list, function) = (elements(arguments, 0)?, function(arguments, 1)?);
+
+    // Each key is computed once, as the function may be expensive or have side effects.
+    let mut keyed = list
+        .into_iter()
+        .map(|element| {
+            Ok((
+                apply("key", &function, vec![element.clone()], context)?,
+                element,
+            ))
+        })
+        .collect::<std::result::Result<Vec<_>, SplashRuntimeError>>()?;
+
+    let mut comparable = true;
+    keyed.sort_by(|(left, _), (right, _)| {
+        order(left, right).unwrap_or_else(|| {
+            comparable = false;
+            Ordering::Equal
+        })
+    });
+
+    if !comparable {
+        let keys = keyed.into_iter().map(|(key, _)| key).collect();
+        return Err(SplashRuntimeError::InvalidSignatureType(
+            "sort_by",
+            vec![Value::List(keys)],
+        ));
+    }
+
+    Ok(Some(Value::List(
+        keyed.into_iter().map(|(_, element)| element).collect(),
+    )))
+}
+
+/// The first element for which a predicate returns `true`, or `nil` if there is none. Given two strings, finds
+/// the position of the second one in the first instead.
+pub fn find<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    if let Some(Value::String(_)) = arguments.first() {
+        return string::find(arguments);
+    }
+
+    expect_arity("find", arguments, Arity::exactly(2))?;
+    let (list, predicate) = (elements(arguments, 0)?, function(arguments, 1)?);
+
+    for element in list {
+        if test(&predicate, element.clone(), context)? {
+            return Ok(Some(element));
+        }
+    }
+
+    Ok(Some(Value::Nil))
+}
+
+/// Whether a predicate returns `true` for any element, or whether any element is `true` without a predicate.
+/// Stops at the first such element.
+pub fn any<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    quantify("any", arguments, true, context)
+}
+
+/// Whether a predicate returns `true` for every element, or whether every element is `true` without a
+/// predicate. Stops at the first element for which it doesn't.
+pub fn all<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    quantify("all", arguments, false, context)
+}
+
+/// Looks for an element whose test gives `wanted`, which decides the result.
+fn quantify<'a>(
+    name: &'a str,
+    arguments: &[Value<'a>],
+    wanted: bool,
+    context: &mut Context<'a>,
+) -> Result<'a> {
+    expect_arity(name, arguments, Arity::between(1, 2))?;
+    let list = elements(arguments, 0)?;
+    let predicate = match arguments.len() {
+        1 => None,
+        _ => Some(function(arguments, 1)?),
+    };
+
+    for element in list {
+        let result = match &predicate {
+            Some(predicate) => test(predicate, element, context)?,
+            None => match element {
+                Value::Boolean(boolean) => boolean,
+                value => return Err(SplashRuntimeError::InvalidPredicate(value)),
+            },
+        };
+
+        if result == wanted {
+            return Ok(Some(Value::Boolean(wanted)));
+        }
+    }
+
+    Ok(Some(Value::Boolean(!wanted)))
+}
+
+/// Groups the elements into a map from the keys a function returns for them to the lists of elements with
+/// that key, in their original order.
+pub fn group_by<'a>(arguments: &[Value<'a>], context: &mut Context<'a>) -> Result<'a> {
+    expect_arity("group_by", arguments, Arity::exactly(2))?;
+    let (list, function) = (elements(arguments, 0)?, function(arguments, 1)?);
+
+    let mut groups: BTreeMap<Key, Vec<Value>> = BTreeMap::new();
+    for element in list {
+        let key = Key::try_from(apply("key", &function, vec![element.clone()], context)?)?;
+        groups.entry(key).or_default().push(element);
+    }
+
+    Ok(Some(Value::Map(
+        groups
+            .into_iter()
+            .map(|(key, group)| (key, Value::List(group)))
+            .collect(),
+    )))
+}
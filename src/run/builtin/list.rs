@@ -0,0 +1,231 @@
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use crate::run::{
+    evaluate::Result, function::Arity, key::Key, utils::list_index, value::Value,
+    SplashRuntimeError,
+};
+
+use super::expect_arity;
+
+/// The elements of the list or range at `position` among the arguments.
+pub(super) fn elements<'a>(
+    arguments: &[Value<'a>],
+    position: usize,
+) -> std::result::Result<Vec<Value<'a>>, SplashRuntimeError<'a>> {
+    match &arguments[position] {
+        Value::List(list) => Ok(list.clone()),
+        Value::Range(range) => Ok(range.iter().map(Value::Integer).collect()),
+        value => Err(SplashRuntimeError::NotAList(value.clone())),
+    }
+}
+
+/// Orders two numbers or two strings, which is `None` for other values and for NaN.
+pub fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Integer(left), Value::Integer(right)) => Some(left.cmp(right)),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (left, right) => {
+            let (left, right) = super::floats(left, right)?;
+            left.partial_cmp(&right)
+        }
+    }
+}
+
+pub fn contains<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("contains", arguments, Arity::exactly(2))?;
+    let list = elements(arguments, 0)?;
+
+    Ok(Some(Value::Boolean(list.contains(&arguments[1]))))
+}
+
+/// The position of the first element equal to a value, or `nil` if there is none.
+pub fn index_of<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("index_of", arguments, Arity::exactly(2))?;
+    let list = elements(arguments, 0)?;
+
+    let position = match list.iter().position(|element| *element == arguments[1]) {
+        Some(position) => Value::Integer(position as i64),
+        None => Value::Nil,
+    };
+
+    Ok(Some(position))
+}
+
+/// Inserts a value before the element at an index, which can be the length of the list to append it.
+pub fn insert<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("insert", arguments, Arity::exactly(3))?;
+    let mut list = elements(arguments, 0)?;
+
+    let index = list_index(arguments[1].clone(), list.len() + 1)?;
+    list.insert(index, arguments[2].clone());
+    Ok(Some(Value::List(list)))
+}
+
+pub fn remove_at<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("remove_at", arguments, Arity::exactly(2))?;
+    let mut list = elements(arguments, 0)?;
+
+    let index = list_index(arguments[1].clone(), list.len())?;
+    list.remove(index);
+    Ok(Some(Value::List(list)))
+}
+
+/// Removes the first element equal to a value, if there is one.
+pub fn remove_value<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("remove_value", arguments, Arity::exactly(2))?;
+    let mut list = elements(arguments, 0)?;
+
+    if let Some(position) = list.iter().position(|element| *element == arguments[1]) {
+        list.remove(position);
+    }
+    Ok(Some(Value::List(list)))
+}
+
+/// Joins any number of lists end to end.
+pub fn concat<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    let mut list = Vec::new();
+    for position in 0..arguments.len() {
+        list.extend(elements(arguments, position)?);
+    }
+
+    Ok(Some(Value::List(list)))
+}
+
+pub fn reverse<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("reverse", arguments, Arity::exactly(1))?;
+    let mut list = elements(arguments, 0)?;
+
+    list.reverse();
+    Ok(Some(Value::List(list)))
+}
+
+/// The elements from `start` up to, but excluding, `end`, which defaults to the end of the list.
+pub fn slice<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("slice", arguments, Arity::between(2, 3))?;
+    let list = elements(arguments, 0)?;
+
+    let start = list_index(arguments[1].clone(), list.len() + 1)?;
+    let end = match arguments.get(2) {
+        Some(end) => list_index(end.clone(), list.len() + 1)?,
+        None => list.len(),
+    };
+
+    if start > end {
+        return Err(SplashRuntimeError::OutOufRange(arguments[1].clone()));
+    }
+
+    Ok(Some(Value::List(list[start..end].to_vec())))
+}
+
+/// Sorts a list of numbers or of strings in ascending order, keeping equal elements in their original order.
+pub fn sort<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("sort", arguments, Arity::exactly(1))?;
+    let mut list = elements(arguments, 0)?;
+
+    let mut comparable = true;
+    list.sort_by(|left, right| {
+        order(left, right).unwrap_or_else(|| {
+            comparable = false;
+            Ordering::Equal
+        })
+    });
+
+    if !comparable {
+        return Err(SplashRuntimeError::InvalidSignatureType(
+            "sort",
+            arguments.to_vec(),
+        ));
+    }
+
+    Ok(Some(Value::List(list)))
+}
+
+/// Removes the elements equal to an earlier one, keeping the first occurrences in order.
+pub fn unique<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("unique", arguments, Arity::exactly(1))?;
+
+    // Values that can be map keys are looked up in a set, and the others are compared one by one.
+    let mut keys = BTreeSet::new();
+    let mut others: Vec<Value> = Vec::new();
+    let mut list = Vec::new();
+
+    for element in elements(arguments, 0)? {
+        let seen = match Key::try_from(element.clone()) {
+            Ok(key) => !keys.insert(key),
+            Err(_) if others.contains(&element) => true,
+            Err(_) => {
+                others.push(element.clone());
+                false
+            }
+        };
+
+        if !seen {
+            list.push(element);
+        }
+    }
+
+    Ok(Some(Value::List(list)))
+}
+
+/// A list made of a value repeated `n` times, called as `repeat_list(value, n)`.
+pub fn repeat_list<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("repeat_list", arguments, Arity::exactly(2))?;
+
+    let times = match arguments[1] {
+        Value::Integer(times) => usize::try_from(times).ok(),
+        _ => None,
+    }
+    .ok_or_else(|| SplashRuntimeError::InvalidSignatureType("repeat_list", arguments.to_vec()))?;
+
+    // Lists too large to allocate fail instead of aborting.
+    let mut list = Vec::new();
+    list.try_reserve_exact(times)
+        .map_err(|_| SplashRuntimeError::Overflow("repeat_list"))?;
+    list.extend(std::iter::repeat_n(arguments[0].clone(), times));
+
+    Ok(Some(Value::List(list)))
+}
+
+/// Replaces the lists and ranges of a list by their elements, one level deep.
+pub fn flatten<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("flatten", arguments, Arity::exactly(1))?;
+
+    let mut list = Vec::new();
+    for element in elements(arguments, 0)? {
+        match element {
+            Value::List(elements) => list.extend(elements),
+            Value::Range(range) => list.extend(range.iter().map(Value::Integer)),
+            element => list.push(element),
+        }
+    }
+
+    Ok(Some(Value::List(list)))
+}
+
+/// Pairs up the elements of lists at the same positions, stopping at the end of the shortest list.
+pub fn zip<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("zip", arguments, Arity::at_least(1))?;
+    let lists = (0..arguments.len())
+        .map(|position| elements(arguments, position))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let length = lists.iter().map(Vec::len).min().unwrap_or(0);
+    let tuples = (0..length)
+        .map(|index| Value::List(lists.iter().map(|list| list[index].clone()).collect()))
+        .collect();
+
+    Ok(Some(Value::List(tuples)))
+}
+
+/// Pairs each element with its position, as `[index, element]`.
+pub fn enumerate<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("enumerate", arguments, Arity::exactly(1))?;
+
+    let pairs = elements(arguments, 0)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, element)| Value::List(vec![Value::Integer(index as i64), element]))
+        .collect();
+
+    Ok(Some(Value::List(pairs)))
+}
@@ -0,0 +1,246 @@
+use std::cmp::Ordering;
+
+use crate::run::{evaluate::Result, function::Arity, value::Value, SplashRuntimeError};
+
+use super::{compare, expect_arity, floats, integer, power};
+
+/// Converts every argument of the function `name` to a float, failing if one of them isn't a number.
+fn numbers<'a>(
+    name: &'a str,
+    arguments: &[Value<'a>],
+) -> std::result::Result<Vec<f64>, SplashRuntimeError<'a>> {
+    arguments
+        .iter()
+        .map(|argument| match argument {
+            Value::Integer(integer) => Ok(*integer as f64),
+            Value::Number(number) => Ok(*number),
+            _ => Err(SplashRuntimeError::InvalidSignatureType(
+                name,
+                arguments.to_vec(),
+            )),
+        })
+        .collect()
+}
+
+/// Applies a float function to the single number given to the function `name`.
+fn unary<'a>(name: &'a str, arguments: &[Value<'a>], function: fn(f64) -> f64) -> Result<'a> {
+    expect_arity(name, arguments, Arity::exactly(1))?;
+    let [number] = numbers(name, arguments)?[..] else {
+        unreachable!("there is exactly one argument")
+    };
+
+    Ok(Some(Value::Number(function(number))))
+}
+
+fn binary<'a>(name: &'a str, arguments: &[Value<'a>], function: fn(f64, f64) -> f64) -> Result<'a> {
+    expect_arity(name, arguments, Arity::exactly(2))?;
+    let [left, right] = numbers(name, arguments)?[..] else {
+        unreachable!("there are exactly two arguments")
+    };
+
+    Ok(Some(Value::Number(function(left, right))))
+}
+
+/// Rounds the single number given to the function `name` to an integer. Integers are left as they are, and
+/// floats that are infinite, NaN or too large for an integer are rejected.
+fn rounding<'a>(name: &'a str, arguments: &[Value<'a>], function: fn(f64) -> f64) -> Result<'a> {
+    expect_arity(name, arguments, Arity::exactly(1))?;
+
+    match arguments[0] {
+        Value::Integer(integer) => Ok(Some(Value::Integer(integer))),
+        Value::Number(number) if number.is_finite() => {
+            let rounded = function(number);
+            // Floats at or beyond 2^63 don't fit, while -2^63 itself does.
+            if (i64::MIN as f64..-(i64::MIN as f64)).contains(&rounded) {
+                Ok(Some(Value::Integer(rounded as i64)))
+            } else {
+                Err(SplashRuntimeError::Overflow(name))
+            }
+        }
+        _ => Err(SplashRuntimeError::InvalidSignatureType(
+            name,
+            arguments.to_vec(),
+        )),
+    }
+}
+
+/// The values that functions such as `min` work on, which are either their arguments or the elements of their
+/// only argument if it is a list or a range.
+fn elements<'a>(arguments: &[Value<'a>]) -> Vec<Value<'a>> {
+    match arguments {
+        [Value::List(list)] => list.clone(),
+        [Value::Range(range)] => range.iter().map(Value::Integer).collect(),
+        arguments => arguments.to_vec(),
+    }
+}
+
+/// Finds the number that comes first in the `wanted` order, the result being NaN if any of the numbers is.
+fn extreme<'a>(name: &'a str, arguments: &[Value<'a>], wanted: Ordering) -> Result<'a> {
+    let mut elements = elements(arguments).into_iter();
+    let Some(mut best) = elements.next() else {
+        return Err(SplashRuntimeError::InvalidSignatureType(
+            name,
+            arguments.to_vec(),
+        ));
+    };
+
+    // A single number is compared with itself so that its type is checked.
+    for element in std::iter::once(best.clone()).chain(elements) {
+        let ordering = compare(name, element.clone(), best.clone())
+            .map_err(|_| SplashRuntimeError::InvalidSignatureType(name, arguments.to_vec()))?;
+
+        match ordering {
+            Some(ordering) if ordering == wanted => best = element,
+            Some(_) => {}
+            None => return Ok(Some(Value::Number(f64::NAN))),
+        }
+    }
+
+    Ok(Some(best))
+}
+
+pub fn abs<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("abs", arguments, Arity::exactly(1))?;
+
+    match arguments[0] {
+        Value::Integer(value) => integer("abs", value.checked_abs()),
+        Value::Number(number) => Ok(Some(Value::Number(number.abs()))),
+        _ => Err(SplashRuntimeError::InvalidSignatureType(
+            "abs",
+            arguments.to_vec(),
+        )),
+    }
+}
+
+pub fn floor<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    rounding("floor", arguments, f64::floor)
+}
+
+pub fn ceil<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    rounding("ceil", arguments, f64::ceil)
+}
+
+/// Rounds to the nearest integer, halfway cases being rounded away from zero.
+pub fn round<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    rounding("round", arguments, f64::round)
+}
+
+/// Rounds toward zero.
+pub fn trunc<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    rounding("trunc", arguments, f64::trunc)
+}
+
+pub fn sqrt<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("sqrt", arguments, f64::sqrt)
+}
+
+/// The same as the `**` operator.
+pub fn pow<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("pow", arguments, Arity::exactly(2))?;
+    power(arguments[0].clone(), arguments[1].clone())
+}
+
+pub fn exp<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("exp", arguments, f64::exp)
+}
+
+pub fn ln<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("ln", arguments, f64::ln)
+}
+
+pub fn log10<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("log10", arguments, f64::log10)
+}
+
+pub fn sin<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("sin", arguments, f64::sin)
+}
+
+pub fn cos<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("cos", arguments, f64::cos)
+}
+
+pub fn tan<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("tan", arguments, f64::tan)
+}
+
+pub fn asin<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("asin", arguments, f64::asin)
+}
+
+pub fn acos<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("acos", arguments, f64::acos)
+}
+
+pub fn atan<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    unary("atan", arguments, f64::atan)
+}
+
+/// The angle of the point `(x, y)`, called as `atan2(y, x)`.
+pub fn atan2<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    binary("atan2", arguments, f64::atan2)
+}
+
+/// The length of the hypotenuse of a right triangle with the two given sides.
+pub fn hypot<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    binary("hypot", arguments, f64::hypot)
+}
+
+/// The smallest of the arguments, or of the elements of a list.
+pub fn min<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("min", arguments, Arity::at_least(1))?;
+
+    extreme("min", arguments, Ordering::Less)
+}
+
+/// The largest of the arguments, or of the elements of a list.
+pub fn max<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("max", arguments, Arity::at_least(1))?;
+
+    extreme("max", arguments, Ordering::Greater)
+}
+
+/// Restricts a number to the bounds `low` and `high`, called as `clamp(number, low, high)`.
+pub fn clamp<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("clamp", arguments, Arity::exactly(3))?;
+    let [number, low, high] = arguments else {
+        unreachable!("there are exactly three arguments")
+    };
+
+    if compare("clamp", low.clone(), high.clone())?.is_some_and(Ordering::is_gt) {
+        return Err(SplashRuntimeError::InvalidSignatureType(
+            "clamp",
+            arguments.to_vec(),
+        ));
+    }
+
+    let clamped = if compare("clamp", number.clone(), low.clone())?.is_some_and(Ordering::is_lt) {
+        low
+    } else if compare("clamp", number.clone(), high.clone())?.is_some_and(Ordering::is_gt) {
+        high
+    } else {
+        number
+    };
+
+    Ok(Some(clamped.clone()))
+}
+
+/// Adds up the arguments, or the elements of a list. The sum stays an integer unless a float is added.
+pub fn sum<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    elements(arguments)
+        .into_iter()
+        .try_fold(Value::Integer(0), |total, element| match (total, element) {
+            (Value::Integer(total), Value::Integer(element)) => total
+                .checked_add(element)
+                .map(Value::Integer)
+                .ok_or(SplashRuntimeError::Overflow("sum")),
+            (total, element) => match floats(&total, &element) {
+                Some((total, element)) => Ok(Value::Number(total + element)),
+                None => Err(SplashRuntimeError::InvalidSignatureType(
+                    "sum",
+                    arguments.to_vec(),
+                )),
+            },
+        })
+        .map(Some)
+}
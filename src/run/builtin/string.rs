@@ -0,0 +1,281 @@
+use crate::run::{evaluate::Result, function::Arity, value::Value, SplashRuntimeError};
+
+use super::expect_arity;
+
+/// The string at `position` among the arguments of the function `name`.
+fn text<'v, 'a>(
+    name: &'a str,
+    arguments: &'v [Value<'a>],
+    position: usize,
+) -> std::result::Result<&'v str, SplashRuntimeError<'a>> {
+    match &arguments[position] {
+        Value::String(string) => Ok(string),
+        _ => Err(SplashRuntimeError::InvalidSignatureType(
+            name,
+            arguments.to_vec(),
+        )),
+    }
+}
+
+/// The non-negative integer at `position` among the arguments of the function `name`.
+fn count<'a>(
+    name: &'a str,
+    arguments: &[Value<'a>],
+    position: usize,
+) -> std::result::Result<usize, SplashRuntimeError<'a>> {
+    match arguments[position] {
+        Value::Integer(integer) => usize::try_from(integer)
+            .map_err(|_| SplashRuntimeError::InvalidSignatureType(name, arguments.to_vec())),
+        _ => Err(SplashRuntimeError::InvalidSignatureType(
+            name,
+            arguments.to_vec(),
+        )),
+    }
+}
+
+/// An empty string with room for `size` bytes, which is `None` if the size overflowed. Sizes too large to
+/// allocate fail with an overflow instead of aborting.
+fn allocate<'a>(
+    name: &'a str,
+    size: Option<usize>,
+) -> std::result::Result<String, SplashRuntimeError<'a>> {
+    let mut string = String::new();
+    size.and_then(|size| string.try_reserve_exact(size).ok())
+        .ok_or(SplashRuntimeError::Overflow(name))?;
+    Ok(string)
+}
+
+/// Applies a transformation to the single string given to the function `name`.
+fn transform<'a>(
+    name: &'a str,
+    arguments: &[Value<'a>],
+    function: fn(&str) -> String,
+) -> Result<'a> {
+    expect_arity(name, arguments, Arity::exactly(1))?;
+    Ok(Some(Value::String(function(text(name, arguments, 0)?))))
+}
+
+fn test<'a>(
+    name: &'a str,
+    arguments: &[Value<'a>],
+    function: fn(&str, &str) -> bool,
+) -> Result<'a> {
+    expect_arity(name, arguments, Arity::exactly(2))?;
+    let (string, other) = (text(name, arguments, 0)?, text(name, arguments, 1)?);
+    Ok(Some(Value::Boolean(function(string, other))))
+}
+
+fn strings<'a, 's>(strings: impl Iterator<Item = &'s str>) -> Value<'a> {
+    Value::List(
+        strings
+            .map(|part| Value::String(part.to_string()))
+            .collect(),
+    )
+}
+
+/// The characters of a string, each as a string of its own.
+pub fn characters<'a>(string: &str) -> impl Iterator<Item = Value<'a>> + '_ {
+    string
+        .chars()
+        .map(|character| Value::String(character.to_string()))
+}
+
+/// Splits a string around a separator, around whitespace if there is none, or into its characters if the
+/// separator is empty.
+pub fn split<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("split", arguments, Arity::between(1, 2))?;
+    let string = text("split", arguments, 0)?;
+
+    let parts = match arguments.len() {
+        1 => strings(string.split_whitespace()),
+        _ => match text("split", arguments, 1)? {
+            "" => Value::List(characters(string).collect()),
+            separator => strings(string.split(separator)),
+        },
+    };
+
+    Ok(Some(parts))
+}
+
+/// Joins the elements of a list into a string, with an optional separator between them. Elements that aren't
+/// strings are written as `string` would write them.
+pub fn join<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("join", arguments, Arity::between(1, 2))?;
+    let Value::List(list) = &arguments[0] else {
+        return Err(SplashRuntimeError::InvalidSignatureType(
+            "join",
+            arguments.to_vec(),
+        ));
+    };
+
+    let separator = match arguments.len() {
+        1 => "",
+        _ => text("join", arguments, 1)?,
+    };
+
+    let joined = list
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(separator);
+
+    Ok(Some(Value::String(joined)))
+}
+
+pub fn trim<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    transform("trim", arguments, |string| string.trim().to_string())
+}
+
+pub fn trim_start<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    transform("trim_start", arguments, |string| {
+        string.trim_start().to_string()
+    })
+}
+
+pub fn trim_end<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    transform("trim_end", arguments, |string| {
+        string.trim_end().to_string()
+    })
+}
+
+pub fn upper<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    transform("upper", arguments, str::to_uppercase)
+}
+
+pub fn lower<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    transform("lower", arguments, str::to_lowercase)
+}
+
+/// Replaces every occurrence of a string by another, called as `replace(string, from, to)`.
+pub fn replace<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("replace", arguments, Arity::exactly(3))?;
+    let string = text("replace", arguments, 0)?;
+    let (from, to) = (
+        text("replace", arguments, 1)?,
+        text("replace", arguments, 2)?,
+    );
+
+    Ok(Some(Value::String(string.replace(from, to))))
+}
+
+pub fn starts_with<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    test("starts_with", arguments, |string, prefix| {
+        string.starts_with(prefix)
+    })
+}
+
+pub fn ends_with<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    test("ends_with", arguments, |string, suffix| {
+        string.ends_with(suffix)
+    })
+}
+
+/// The position in characters of the first occurrence of a string in another, or `nil` if there is none.
+pub fn find<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("find", arguments, Arity::exactly(2))?;
+    let (string, pattern) = (text("find", arguments, 0)?, text("find", arguments, 1)?);
+
+    let position = match string.find(pattern) {
+        Some(byte) => Value::Integer(string[..byte].chars().count() as i64),
+        None => Value::Nil,
+    };
+
+    Ok(Some(position))
+}
+
+pub fn repeat<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("repeat", arguments, Arity::exactly(2))?;
+    let (string, times) = (
+        text("repeat", arguments, 0)?,
+        count("repeat", arguments, 1)?,
+    );
+
+    let mut repeated = allocate("repeat", string.len().checked_mul(times))?;
+    if !string.is_empty() {
+        for _ in 0..times {
+            repeated.push_str(string);
+        }
+    }
+
+    Ok(Some(Value::String(repeated)))
+}
+
+/// Pads a string up to `width` characters with a fill character, which is a space unless one is given. Strings
+/// that are already long enough are left as they are.
+fn pad<'a>(name: &'a str, arguments: &[Value<'a>], left: bool) -> Result<'a> {
+    expect_arity(name, arguments, Arity::between(2, 3))?;
+    let (string, width) = (text(name, arguments, 0)?, count(name, arguments, 1)?);
+
+    let fill = match arguments.len() {
+        2 => ' ',
+        _ => {
+            let mut fill = text(name, arguments, 2)?.chars();
+            match (fill.next(), fill.next()) {
+                (Some(character), None) => character,
+                _ => {
+                    return Err(SplashRuntimeError::InvalidSignatureType(
+                        name,
+                        arguments.to_vec(),
+                    ))
+                }
+            }
+        }
+    };
+
+    let missing = width.saturating_sub(string.chars().count());
+    let size = fill
+        .len_utf8()
+        .checked_mul(missing)
+        .and_then(|padding| padding.checked_add(string.len()));
+
+    let mut padded = allocate(name, size)?;
+    if !left {
+        padded.push_str(string);
+    }
+    padded.extend(std::iter::repeat_n(fill, missing));
+    if left {
+        padded.push_str(string);
+    }
+
+    Ok(Some(Value::String(padded)))
+}
+
+pub fn pad_left<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    pad("pad_left", arguments, true)
+}
+
+pub fn pad_right<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    pad("pad_right", arguments, false)
+}
+
+pub fn chars<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("chars", arguments, Arity::exactly(1))?;
+    Ok(Some(Value::List(
+        characters(text("chars", arguments, 0)?).collect(),
+    )))
+}
+
+/// The characters of a string from `start` up to, but excluding, `end`, which defaults to the end of the
+/// string.
+pub fn substring<'a>(arguments: &[Value<'a>]) -> Result<'a> {
+    expect_arity("substring", arguments, Arity::between(2, 3))?;
+    let string = text("substring", arguments, 0)?;
+    let length = string.chars().count();
+
+    let start = count("substring", arguments, 1)?;
+    let end = match arguments.len() {
+        2 => length,
+        _ => count("substring", arguments, 2)?,
+    };
+
+    if end > length {
+        return Err(SplashRuntimeError::OutOufRange(arguments[2].clone()));
+    }
+    if start > end {
+        return Err(SplashRuntimeError::OutOufRange(arguments[1].clone()));
+    }
+
+    Ok(Some(Value::String(
+        string.chars().skip(start).take(end - start).collect(),
+    )))
+}
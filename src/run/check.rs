@@ -0,0 +1,332 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use crate::parse::{
+    is_capitalized, Accessor, Argument, Block, Expression, Identifier, Operand, Parameter, Pattern,
+    Program, Statement, StringPart,
+};
+
+use super::{context::constants, SplashRuntimeError};
+
+/// Finds the assignments to constants and the structures that can't be constructed of a program before it
+/// runs. Names are resolved the way the runtime
+/// resolves them, so an assignment is only rejected if it is certain to target a constant. A constant that its
+/// block declares again as a variable is never certain, since a function may run after the new declaration,
+/// and assignments to it are left to the runtime.
+pub fn check<'a>(program: &Program<'a>) -> Result<(), SplashRuntimeError<'a>> {
+    let prelude = constants()
+        .into_iter()
+        .map(|(identifier, _)| (identifier, true))
+        .collect();
+
+    Checker {
+        scopes: vec![prelude],
+    }
+    .scoped(&[], program)
+}
+
+/// The names declared in each enclosing scope, from the outermost to the innermost, mapped to whether they
+/// are constants.
+struct Checker<'a> {
+    scopes: Vec<HashMap<Identifier<'a>, bool>>,
+}
+
+impl<'a> Checker<'a> {
+    fn declare(&mut self, identifier: Identifier<'a>, constant: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(identifier, constant);
+        }
+    }
+
+    fn assign(&self, identifier: Identifier<'a>) -> Result<(), SplashRuntimeError<'a>> {
+        let constant = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(identifier));
+
+        match constant {
+            Some(true) => Err(SplashRuntimeError::AssignedConstant(identifier)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks a block in a new scope, which starts with the variables `bindings`.
+    fn scoped(
+        &mut self,
+        bindings: &[Identifier<'a>],
+        block: &Block<'a>,
+    ) -> Result<(), SplashRuntimeError<'a>> {
+        self.scopes.push(HashMap::new());
+        for identifier in bindings {
+            self.declare(identifier, false);
+        }
+
+        let result = self.block(block);
+        self.scopes.pop();
+        result
+    }
+
+    fn block(&mut self, block: &Block<'a>) -> Result<(), SplashRuntimeError<'a>> {
+        let redeclared = block
+            .statements()
+            .iter()
+            .flat_map(declarations)
+            .collect::<HashSet<_>>();
+
+        block
+            .statements()
+            .iter()
+            .try_for_each(|statement| self.statement(statement, &redeclared))
+    }
+
+    /// Checks a function body, whose parameters are bound one after the other so that defaults can refer to
+    /// the earlier parameters.
+    fn function(
+        &mut self,
+        parameters: &[Parameter<'a>],
+        body: &Block<'a>,
+    ) -> Result<(), SplashRuntimeError<'a>> {
+        self.scopes.push(HashMap::new());
+
+        let result = parameters
+            .iter()
+            .try_for_each(|parameter| {
+                if let Some(default) = parameter.default() {
+                    self.expression(default)?;
+                }
+
+                self.declare(parameter.name(), false);
+                Ok(())
+            })
+            .and_then(|()| self.block(body));
+
+        self.scopes.pop();
+        result
+    }
+
+    /// Checks a statement of a block, which declares the variables `redeclared` somewhere.
+    fn statement(
+        &mut self,
+        statement: &Statement<'a>,
+        redeclared: &HashSet<Identifier<'a>>,
+    ) -> Result<(), SplashRuntimeError<'a>> {
+        match statement {
+            Statement::Simple(expression) | Statement::Throw(expression) => {
+                self.expression(expression)
+            }
+            Statement::Return(expression) => expression
+                .iter()
+                .try_for_each(|expression| self.expression(expression)),
+            Statement::Block(block) => self.scoped(&[], block),
+            Statement::Initialization(pattern, expression) => {
+                self.expression(expression)?;
+                for identifier in bindings(pattern) {
+                    self.declare(identifier, false);
+                }
+
+                Ok(())
+            }
+            Statement::Constant(identifier, expression) => {
+                self.expression(expression)?;
+                self.declare(identifier, !redeclared.contains(identifier));
+                Ok(())
+            }
+            Statement::Assignment(identifier, expression)
+            | Statement::CompoundAssignment(identifier, _, expression) => {
+                self.expression(expression)?;
+                self.assign(identifier)
+            }
+            Statement::IndexAssignment(identifier, accessors, expression)
+            | Statement::CompoundIndexAssignment(identifier, accessors, _, expression) => {
+                self.accessors(accessors)?;
+                self.expression(expression)?;
+                self.assign(identifier)
+            }
+            Statement::Destructuring(pattern, expression) => {
+                self.expression(expression)?;
+                bindings(pattern)
+                    .into_iter()
+                    .try_for_each(|identifier| self.assign(identifier))
+            }
+            Statement::If(branches, otherwise) => {
+                for (predicate, then) in branches {
+                    self.expression(predicate)?;
+                    self.scoped(&[], then)?;
+                }
+
+                otherwise
+                    .iter()
+                    .try_for_each(|otherwise| self.scoped(&[], otherwise))
+            }
+            Statement::While(_, predicate, body) => {
+                self.expression(predicate)?;
+                self.scoped(&[], body)
+            }
+            Statement::For(_, pattern, iterable, body) => {
+                self.expression(iterable)?;
+                self.scoped(&bindings(pattern), body)
+            }
+            Statement::Definition(identifier, parameters, body) => {
+                // The function is declared before its body is checked, as it can call itself.
+                self.declare(identifier, false);
+                self.function(parameters, body)
+            }
+            Statement::Structure(identifier, _) if !is_capitalized(identifier) => {
+                Err(SplashRuntimeError::UncapitalizedStructure(identifier))
+            }
+            Statement::Structure(identifier, _) | Statement::Enumeration(identifier, _) => {
+                self.declare(identifier, false);
+                Ok(())
+            }
+            Statement::Match(expression, arms) => {
+                self.expression(expression)?;
+                for arm in arms {
+                    self.scopes.push(HashMap::new());
+                    for identifier in bindings(arm.pattern()) {
+                        self.declare(identifier, false);
+                    }
+
+                    let result = arm
+                        .guard()
+                        .map_or(Ok(()), |guard| self.expression(guard))
+                        .and_then(|()| self.block(arm.body()));
+                    self.scopes.pop();
+                    result?;
+                }
+
+                Ok(())
+            }
+            Statement::Try(body, catch, finally) => {
+                self.scoped(&[], body)?;
+                if let Some((identifier, handler)) = catch {
+                    let bindings: Vec<_> = identifier.iter().copied().collect();
+                    self.scoped(&bindings, handler)?;
+                }
+
+                finally
+                    .iter()
+                    .try_for_each(|finally| self.scoped(&[], finally))
+            }
+            Statement::Import(path, alias) => {
+                self.declare(alias.unwrap_or_else(|| module_name(path)), false);
+                Ok(())
+            }
+            Statement::ImportFrom(identifiers, _) => {
+                for identifier in identifiers {
+                    self.declare(identifier, false);
+                }
+
+                Ok(())
+            }
+            Statement::Break(_) | Statement::Continue(_) => Ok(()),
+        }
+    }
+
+    fn accessors(&mut self, accessors: &[Accessor<'a>]) -> Result<(), SplashRuntimeError<'a>> {
+        accessors.iter().try_for_each(|accessor| match accessor {
+            Accessor::Index(index) => self.expression(index),
+            Accessor::Field(_) => Ok(()),
+        })
+    }
+
+    fn arguments(&mut self, arguments: &[Argument<'a>]) -> Result<(), SplashRuntimeError<'a>> {
+        arguments.iter().try_for_each(|argument| match argument {
+            Argument::Positional(expression) | Argument::Keyword(_, expression) => {
+                self.expression(expression)
+            }
+        })
+    }
+
+    fn operand(&mut self, operand: &Operand<'a>) -> Result<(), SplashRuntimeError<'a>> {
+        match operand {
+            Operand::Operation(operation) => operation
+                .operands()
+                .iter()
+                .try_for_each(|operand| self.operand(operand)),
+            Operand::Expression(expression) => self.expression(expression),
+        }
+    }
+
+    /// Expressions don't assign variables themselves, but the bodies of the lambdas they contain can.
+    fn expression(&mut self, expression: &Expression<'a>) -> Result<(), SplashRuntimeError<'a>> {
+        match expression {
+            Expression::Atom(_) => Ok(()),
+            Expression::Operation(operation) => operation
+                .operands()
+                .iter()
+                .try_for_each(|operand| self.operand(operand)),
+            Expression::Function(_, arguments) => self.arguments(arguments),
+            Expression::Call(function, arguments) => {
+                self.expression(function)?;
+                self.arguments(arguments)
+            }
+            Expression::List(elements) => elements
+                .iter()
+                .try_for_each(|element| self.expression(element)),
+            Expression::Map(entries) => entries.iter().try_for_each(|(key, value)| {
+                self.expression(key)?;
+                self.expression(value)
+            }),
+            Expression::Index(collection, index) => {
+                self.expression(collection)?;
+                self.expression(index)
+            }
+            Expression::Method(receiver, _, arguments) => {
+                self.expression(receiver)?;
+                self.arguments(arguments)
+            }
+            Expression::Field(instance, _) => self.expression(instance),
+            Expression::Construction(_, fields) => fields
+                .iter()
+                .try_for_each(|(_, value)| self.expression(value)),
+            Expression::Lambda(parameters, body) => self.function(parameters, body),
+            Expression::Interpolation(parts) => parts.iter().try_for_each(|part| match part {
+                StringPart::Literal(_) => Ok(()),
+                StringPart::Expression(expression) => self.expression(expression),
+            }),
+        }
+    }
+}
+
+/// The name an import without an alias binds, which is the file name without its extension.
+fn module_name(path: &str) -> Identifier<'_> {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path)
+}
+
+/// The variables a statement declares in its block, which doesn't include constants.
+fn declarations<'a>(statement: &Statement<'a>) -> Vec<Identifier<'a>> {
+    match statement {
+        Statement::Initialization(pattern, _) => bindings(pattern),
+        Statement::Definition(identifier, _, _)
+        | Statement::Structure(identifier, _)
+        | Statement::Enumeration(identifier, _) => vec![identifier],
+        Statement::Import(path, alias) => vec![alias.unwrap_or_else(|| module_name(path))],
+        Statement::ImportFrom(identifiers, _) => identifiers.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// The variables bound by a pattern.
+fn bindings<'a>(pattern: &Pattern<'a>) -> Vec<Identifier<'a>> {
+    match pattern {
+        Pattern::Wildcard | Pattern::Literal(_) | Pattern::Rest(None) => Vec::new(),
+        Pattern::Binding(identifier) | Pattern::Rest(Some(identifier)) => vec![identifier],
+        Pattern::List(patterns) | Pattern::Variant(_, _, patterns) => {
+            patterns.iter().flat_map(bindings).collect()
+        }
+        Pattern::Structure(_, fields) => fields
+            .iter()
+            .flat_map(|(_, pattern)| bindings(pattern))
+            .collect(),
+        Pattern::Map(entries) => entries
+            .iter()
+            .flat_map(|(_, pattern)| bindings(pattern))
+            .collect(),
+    }
+}
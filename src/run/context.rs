@@ -1,12 +1,52 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use crate::parse::{Block, Identifier};
+use crate::parse::{Block, Identifier, Parameter};
 
-use super::{builtin, function::Function, value::Value, SplashRuntimeError};
+use super::{
+    builtin,
+    function::{BuiltIn, Function, HigherOrder},
+    module::Modules,
+    structure::Structure,
+    value::Value,
+    SplashRuntimeError,
+};
+
+/// A shared handle on a scope, which closures keep alive after the block that created it has ended.
+pub type Environment<'a> = Rc<RefCell<Scope<'a>>>;
+
+#[derive(Default)]
+pub struct Scope<'a> {
+    variables: HashMap<Identifier<'a>, Value<'a>>,
+    /// The variables of this scope that cannot be assigned to.
+    constants: HashSet<Identifier<'a>>,
+    parent: Option<Environment<'a>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(parent: Environment<'a>) -> Environment<'a> {
+        Rc::new(RefCell::new(Self {
+            variables: HashMap::new(),
+            constants: HashSet::new(),
+            parent: Some(parent),
+        }))
+    }
+
+    /// Returns the variable `identifier` if it is defined in this very scope.
+    pub fn get(&self, identifier: Identifier<'a>) -> Option<Value<'a>> {
+        self.variables.get(identifier).cloned()
+    }
+}
 
 pub struct Context<'a> {
-    variables: Vec<HashMap<Identifier<'a>, Value>>,
-    functions: Vec<HashMap<Identifier<'a>, Function<'a>>>,
+    scope: Environment<'a>,
+    /// The file being run, if any.
+    path: Option<PathBuf>,
+    modules: Rc<RefCell<Modules<'a>>>,
 }
 
 impl<'a> Context<'a> {
@@ -14,90 +54,294 @@ impl<'a> Context<'a> {
         Self::default()
     }
 
-    pub fn variable(&self, identifier: Identifier<'a>) -> Result<Value, SplashRuntimeError<'a>> {
-        for scope in self.variables.iter().rev() {
-            match scope.get(identifier) {
-                Some(value) => return Ok(value.clone()),
-                None => continue,
+    /// Creates the context of the module at `path`, which shares the `modules` of the rest of the program.
+    pub fn module(path: PathBuf, modules: Rc<RefCell<Modules<'a>>>) -> Self {
+        Self {
+            path: Some(path),
+            modules,
+            ..Self::default()
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.modules.borrow_mut().enter(&path);
+        self.path = Some(path);
+    }
+
+    pub fn modules(&self) -> Rc<RefCell<Modules<'a>>> {
+        Rc::clone(&self.modules)
+    }
+
+    pub fn environment(&self) -> Environment<'a> {
+        Rc::clone(&self.scope)
+    }
+
+    pub fn variable(
+        &self,
+        identifier: Identifier<'a>,
+    ) -> Result<Value<'a>, SplashRuntimeError<'a>> {
+        let mut scope = Some(self.environment());
+
+        while let Some(current) = scope {
+            if let Some(value) = current.borrow().variables.get(identifier) {
+                return Ok(value.clone());
             }
+
+            scope = current.borrow().parent.clone();
         }
 
-        return Err(SplashRuntimeError::NotDefined(identifier));
+        Err(SplashRuntimeError::NotDefined(identifier))
     }
 
+    /// Looks up a function to call by name. Values that are not functions are skipped, so that a variable
+    /// doesn't prevent calling a function of the same name from an outer scope.
     pub fn function(
         &self,
         identifier: Identifier<'a>,
-    ) -> Result<&Function<'a>, SplashRuntimeError<'a>> {
-        for scope in self.functions.iter().rev() {
-            match scope.get(identifier) {
-                Some(value) => return Ok(value),
-                None => continue,
+    ) -> Result<Rc<Function<'a>>, SplashRuntimeError<'a>> {
+        let mut scope = Some(self.environment());
+        let mut shadowed = None;
+
+        while let Some(current) = scope {
+            match current.borrow().variables.get(identifier) {
+                Some(Value::Function(function)) => return Ok(Rc::clone(function)),
+                Some(value) => {
+                    shadowed.get_or_insert_with(|| value.clone());
+                }
+                None => {}
             }
+
+            scope = current.borrow().parent.clone();
         }
 
-        return Err(SplashRuntimeError::NotDefined(identifier));
+        Err(match shadowed {
+            Some(value) => SplashRuntimeError::NotAFunction(value),
+            None => SplashRuntimeError::NotDefined(identifier),
+        })
     }
 
-    pub fn initialize_variable(&mut self, identifier: Identifier<'a>, value: Value) {
-        self.variables.last_mut().unwrap().insert(identifier, value);
+    /// Declares a variable in the current scope, shadowing any variable or constant of the same name.
+    pub fn initialize_variable(&mut self, identifier: Identifier<'a>, value: Value<'a>) {
+        let mut scope = self.scope.borrow_mut();
+        scope.constants.remove(identifier);
+        scope.variables.insert(identifier, value);
+    }
+
+    pub fn initialize_constant(&mut self, identifier: Identifier<'a>, value: Value<'a>) {
+        let mut scope = self.scope.borrow_mut();
+        scope.constants.insert(identifier);
+        scope.variables.insert(identifier, value);
     }
 
     pub fn assign_variable(
         &mut self,
         identifier: Identifier<'a>,
-        value: Value,
+        value: Value<'a>,
     ) -> Result<(), SplashRuntimeError<'a>> {
-        for scope in self.variables.iter_mut().rev() {
-            if let Entry::Occupied(mut entry) = scope.entry(identifier) {
-                entry.insert(value);
+        let mut scope = Some(self.environment());
+
+        while let Some(current) = scope {
+            let mut current_scope = current.borrow_mut();
+            let Scope {
+                variables,
+                constants,
+                ..
+            } = &mut *current_scope;
+
+            if let Some(variable) = variables.get_mut(identifier) {
+                if constants.contains(identifier) {
+                    return Err(SplashRuntimeError::AssignedConstant(identifier));
+                }
+
+                *variable = value;
                 return Ok(());
             }
+            drop(current_scope);
+
+            scope = current.borrow().parent.clone();
         }
 
-        return Err(SplashRuntimeError::NotDefined(identifier));
+        Err(SplashRuntimeError::NotDefined(identifier))
     }
 
+    /// Defines a named function in the current scope, which it keeps alive through a reference cycle as
+    /// explained on `Function::Custom`.
     pub fn initialize_function(
         &mut self,
         identifier: Identifier<'a>,
-        arguments: Vec<Identifier<'a>>,
+        parameters: Vec<Parameter<'a>>,
         body: Block<'a>,
     ) {
-        self.functions
-            .last_mut()
-            .unwrap()
-            .insert(identifier, Function::Custom(arguments, body));
+        self.initialize_variable(
+            identifier,
+            Value::Function(Rc::new(Function::Custom(
+                parameters,
+                body,
+                self.environment(),
+            ))),
+        );
     }
 
     pub fn child<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut Self) -> R,
     {
-        self.variables.push(HashMap::new());
-        self.functions.push(HashMap::new());
+        let scope = Scope::new(self.environment());
+        self.within(scope, f)
+    }
 
-        let result = f(self);
+    /// Runs `f` in a new scope whose parent is `environment` instead of the current scope.
+    pub fn child_of<F, R>(&mut self, environment: Environment<'a>, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.within(Scope::new(environment), f)
+    }
 
-        self.variables.pop();
-        self.functions.pop();
+    fn within<F, R>(&mut self, scope: Environment<'a>, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let previous = std::mem::replace(&mut self.scope, scope);
+        let result = f(self);
+        self.scope = previous;
 
         result
     }
 }
 
+/// The constants of the prelude, which can be shadowed but not assigned to.
+pub fn constants<'a>() -> [(Identifier<'a>, Value<'a>); 6] {
+    // The largest integer below which every integer can be represented exactly by a float.
+    const MAX_SAFE_INTEGER: i64 = (1 << f64::MANTISSA_DIGITS) - 1;
+
+    [
+        ("INF", Value::Number(f64::INFINITY)),
+        ("NAN", Value::Number(f64::NAN)),
+        ("PI", Value::Number(std::f64::consts::PI)),
+        ("E", Value::Number(std::f64::consts::E)),
+        ("MAX_SAFE_INTEGER", Value::Integer(MAX_SAFE_INTEGER)),
+        ("MIN_SAFE_INTEGER", Value::Integer(-MAX_SAFE_INTEGER)),
+    ]
+}
+
 impl<'a> Default for Context<'a> {
     fn default() -> Self {
+        let builtins: [(Identifier, BuiltIn<'a>); 66] = [
+            ("print", builtin::print),
+            ("string", builtin::string),
+            ("length", builtin::length),
+            ("push", builtin::push),
+            ("pop", builtin::pop),
+            ("range", builtin::range),
+            ("list", builtin::list),
+            ("keys", builtin::keys),
+            ("values", builtin::values),
+            ("entries", builtin::entries),
+            ("has", builtin::has),
+            ("remove", builtin::remove),
+            ("is_nan", builtin::is_nan),
+            ("is_finite", builtin::is_finite),
+            ("is_integer", builtin::is_integer),
+            ("abs", builtin::math::abs),
+            ("floor", builtin::math::floor),
+            ("ceil", builtin::math::ceil),
+            ("round", builtin::math::round),
+            ("trunc", builtin::math::trunc),
+            ("sqrt", builtin::math::sqrt),
+            ("pow", builtin::math::pow),
+            ("exp", builtin::math::exp),
+            ("ln", builtin::math::ln),
+            ("log10", builtin::math::log10),
+            ("sin", builtin::math::sin),
+            ("cos", builtin::math::cos),
+            ("tan", builtin::math::tan),
+            ("asin", builtin::math::asin),
+            ("acos", builtin::math::acos),
+            ("atan", builtin::math::atan),
+            ("atan2", builtin::math::atan2),
+            ("hypot", builtin::math::hypot),
+            ("min", builtin::math::min),
+            ("max", builtin::math::max),
+            ("clamp", builtin::math::clamp),
+            ("sum", builtin::math::sum),
+            ("split", builtin::string::split),
+            ("join", builtin::string::join),
+            ("trim", builtin::string::trim),
+            ("trim_start", builtin::string::trim_start),
+            ("trim_end", builtin::string::trim_end),
+            ("replace", builtin::string::replace),
+            ("starts_with", builtin::string::starts_with),
+            ("ends_with", builtin::string::ends_with),
+            ("upper", builtin::string::upper),
+            ("lower", builtin::string::lower),
+            ("repeat", builtin::string::repeat),
+            ("pad_left", builtin::string::pad_left),
+            ("pad_right", builtin::string::pad_right),
+            ("chars", builtin::string::chars),
+            ("substring", builtin::string::substring),
+            ("contains", builtin::list::contains),
+            ("index_of", builtin::list::index_of),
+            ("insert", builtin::list::insert),
+            ("remove_at", builtin::list::remove_at),
+            ("remove_value", builtin::list::remove_value),
+            ("concat", builtin::list::concat),
+            ("reverse", builtin::list::reverse),
+            ("slice", builtin::list::slice),
+            ("sort", builtin::list::sort),
+            ("unique", builtin::list::unique),
+            ("repeat_list", builtin::list::repeat_list),
+            ("flatten", builtin::list::flatten),
+            ("zip", builtin::list::zip),
+            ("enumerate", builtin::list::enumerate),
+        ];
+
+        let higher_order: [(Identifier, HigherOrder<'a>); 8] = [
+            ("map", builtin::higher_order::map),
+            ("filter", builtin::higher_order::filter),
+            ("reduce", builtin::higher_order::reduce),
+            ("sort_by", builtin::higher_order::sort_by),
+            ("find", builtin::higher_order::find),
+            ("any", builtin::higher_order::any),
+            ("all", builtin::higher_order::all),
+            ("group_by", builtin::higher_order::group_by),
+        ];
+
+        let mut variables: HashMap<_, _> = builtins
+            .into_iter()
+            .map(|(identifier, function)| (identifier, Function::BuiltIn(function)))
+            .chain(
+                higher_order
+                    .into_iter()
+                    .map(|(identifier, function)| (identifier, Function::HigherOrder(function))),
+            )
+            .map(|(identifier, function)| (identifier, Value::Function(Rc::new(function))))
+            .collect();
+
+        variables.insert("Error", Value::Structure(Rc::new(Structure::error())));
+
+        let constants = constants();
+        let names = constants
+            .iter()
+            .map(|(identifier, _)| *identifier)
+            .collect();
+        variables.extend(constants);
+
+        let prelude = Rc::new(RefCell::new(Scope {
+            variables,
+            constants: names,
+            parent: None,
+        }));
+
+        // User definitions live in their own scope so that they shadow built-ins instead of replacing them.
         Self {
-            variables: vec![HashMap::from([])],
-            functions: vec![HashMap::from([
-                ("print", Function::BuiltIn(builtin::print)),
-                ("string", Function::BuiltIn(builtin::string)),
-                ("length", Function::BuiltIn(builtin::length)),
-                ("push", Function::BuiltIn(builtin::push)),
-                ("pop", Function::BuiltIn(builtin::pop)),
-                ("range", Function::BuiltIn(builtin::range)),
-            ])],
+            scope: Scope::new(prelude),
+            path: None,
+            modules: Rc::default(),
         }
     }
 }
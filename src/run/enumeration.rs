@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use crate::parse::Identifier;
+
+use super::{function::Arity, value::Value, SplashRuntimeError};
+
+/// A user-defined tagged union, whose variants each hold a fixed number of values.
+#[derive(PartialEq, Debug)]
+pub struct Enumeration<'a> {
+    name: Identifier<'a>,
+    variants: Vec<(Identifier<'a>, Vec<Identifier<'a>>)>,
+}
+
+impl<'a> Enumeration<'a> {
+    pub fn new(name: Identifier<'a>, variants: Vec<(Identifier<'a>, Vec<Identifier<'a>>)>) -> Self {
+        Self { name, variants }
+    }
+
+    pub fn name(&self) -> Identifier<'a> {
+        self.name
+    }
+
+    /// Returns the position of the variant called `name`.
+    pub fn variant(&self, name: Identifier<'a>) -> Result<usize, SplashRuntimeError<'a>> {
+        self.variants
+            .iter()
+            .position(|(variant, _)| *variant == name)
+            .ok_or(SplashRuntimeError::UnknownVariant(self.name, name))
+    }
+
+    pub fn variant_name(&self, variant: usize) -> Identifier<'a> {
+        self.variants[variant].0
+    }
+
+    /// Checks that `values` are the right number of values for `variant`.
+    pub fn instantiate(
+        &self,
+        variant: usize,
+        values: Vec<Value<'a>>,
+    ) -> Result<Vec<Value<'a>>, SplashRuntimeError<'a>> {
+        let (name, fields) = &self.variants[variant];
+
+        if values.len() == fields.len() {
+            Ok(values)
+        } else {
+            Err(SplashRuntimeError::InvalidSignature(
+                name,
+                Arity::exactly(fields.len()),
+                values.len(),
+            ))
+        }
+    }
+}
+
+impl Display for Enumeration<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
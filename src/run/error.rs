@@ -1,21 +1,116 @@
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, rc::Rc};
 
 use crate::parse::Identifier;
 
-use super::value::Value;
+use super::{function::Arity, structure::Structure, value::Value};
 
 #[derive(Debug)]
 pub enum SplashRuntimeError<'a> {
     NotDefined(Identifier<'a>),
+    AssignedConstant(Identifier<'a>),
     NoValue,
     InvalidType(Identifier<'a>),
-    InvalidSignature(Identifier<'a>, usize, usize),
-    InvalidSignatureType(Identifier<'a>, Vec<Value>),
-    InvalidPredicate(Value),
+    InvalidSignature(Identifier<'a>, Arity, usize),
+    InvalidSignatureType(Identifier<'a>, Vec<Value<'a>>),
+    UnknownParameter(Identifier<'a>, Identifier<'a>),
+    DuplicateArgument(Identifier<'a>, Identifier<'a>),
+    InvalidPredicate(Value<'a>),
     DivisionByZero,
-    NotAList(Value),
-    NotAnIndex(Value),
-    OutOufRange(Value),
+    Overflow(Identifier<'a>),
+    InvalidStep,
+    OutsideLoop(Identifier<'a>),
+    UndefinedLabel(Identifier<'a>),
+    NotAList(Value<'a>),
+    NotAMap(Value<'a>),
+    NotAFunction(Value<'a>),
+    NoMethod(Value<'a>, Identifier<'a>),
+    NotAStructure(Value<'a>),
+    UncapitalizedStructure(Identifier<'a>),
+    NoField(Value<'a>, Identifier<'a>),
+    UnknownField(Identifier<'a>, Identifier<'a>),
+    MissingField(Identifier<'a>, Identifier<'a>),
+    DuplicateField(Identifier<'a>, Identifier<'a>),
+    NotAnEnumeration(Value<'a>),
+    UnknownVariant(Identifier<'a>, Identifier<'a>),
+    NoMatch(Value<'a>),
+    PatternMismatch(Value<'a>),
+    LengthMismatch(usize, usize),
+    TooFewElements(usize, usize),
+    NotIndexable(Value<'a>),
+    NotAnIndex(Value<'a>),
+    NotAKey(Value<'a>),
+    MissingKey(Value<'a>),
+    OutOufRange(Value<'a>),
+    IndexLevel(usize, Box<SplashRuntimeError<'a>>),
+    Thrown(Value<'a>),
+    InvalidImport(&'a str, String),
+    ImportCycle(Vec<String>),
+    NotExported(Identifier<'a>, Identifier<'a>),
+}
+
+impl<'a> SplashRuntimeError<'a> {
+    /// The name of the kind of error, as seen by scripts that catch it.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotDefined(..) => "NotDefined",
+            Self::AssignedConstant(..) => "AssignedConstant",
+            Self::NoValue => "NoValue",
+            Self::InvalidType(..) => "InvalidType",
+            Self::InvalidSignature(..) => "InvalidSignature",
+            Self::InvalidSignatureType(..) => "InvalidSignatureType",
+            Self::UnknownParameter(..) => "UnknownParameter",
+            Self::DuplicateArgument(..) => "DuplicateArgument",
+            Self::InvalidPredicate(..) => "InvalidPredicate",
+            Self::DivisionByZero => "DivisionByZero",
+            Self::Overflow(..) => "Overflow",
+            Self::InvalidStep => "InvalidStep",
+            Self::OutsideLoop(..) => "OutsideLoop",
+            Self::UndefinedLabel(..) => "UndefinedLabel",
+            Self::NotAList(..) => "NotAList",
+            Self::NotAMap(..) => "NotAMap",
+            Self::NotAFunction(..) => "NotAFunction",
+            Self::NoMethod(..) => "NoMethod",
+            Self::NotAStructure(..) => "NotAStructure",
+            Self::UncapitalizedStructure(..) => "UncapitalizedStructure",
+            Self::NoField(..) => "NoField",
+            Self::UnknownField(..) => "UnknownField",
+            Self::MissingField(..) => "MissingField",
+            Self::DuplicateField(..) => "DuplicateField",
+            Self::NotAnEnumeration(..) => "NotAnEnumeration",
+            Self::UnknownVariant(..) => "UnknownVariant",
+            Self::NoMatch(..) => "NoMatch",
+            Self::PatternMismatch(..) => "PatternMismatch",
+            Self::LengthMismatch(..) => "LengthMismatch",
+            Self::TooFewElements(..) => "TooFewElements",
+            Self::NotIndexable(..) => "NotIndexable",
+            Self::NotAnIndex(..) => "NotAnIndex",
+            Self::NotAKey(..) => "NotAKey",
+            Self::MissingKey(..) => "MissingKey",
+            Self::OutOufRange(..) => "OutOufRange",
+            Self::IndexLevel(_, error) => error.kind(),
+            Self::Thrown(..) => "Thrown",
+            Self::InvalidImport(..) => "InvalidImport",
+            Self::ImportCycle(..) => "ImportCycle",
+            Self::NotExported(..) => "NotExported",
+        }
+    }
+
+    /// Converts the error into the value bound by a `catch` block: thrown values are caught as they are, and
+    /// other errors as an `Error` struct holding their kind and message.
+    #[must_use]
+    pub fn into_value(self) -> Value<'a> {
+        match self {
+            Self::Thrown(value) => value,
+            error => Value::Instance(
+                Rc::new(Structure::error()),
+                vec![
+                    Value::String(String::from(error.kind())),
+                    Value::String(error.to_string()),
+                ],
+            ),
+        }
+    }
 }
 
 impl Display for SplashRuntimeError<'_> {
@@ -25,15 +120,45 @@ impl Display for SplashRuntimeError<'_> {
             "{}",
             match self {
                 Self::NotDefined(identifier) => format!("'{identifier}' is not defined."),
+                Self::AssignedConstant(identifier) => format!("'{identifier}' is a constant and cannot be assigned to."),
                 Self::NoValue => String::from("Expected a value, but the expression returned nothing."),
                 Self::InvalidType(identifier) => format!("'{identifier}' is of the wrong type."),
-                Self::InvalidSignature(identifier, expected, actual) => format!("Function '{identifier}' takes {expected} arguments, but {actual} were provided."),
+                Self::InvalidSignature(identifier, arity, actual) => format!("Function '{identifier}' takes {arity}, but {actual} were provided."),
                 Self::InvalidSignatureType(identifier, parameters) => format!("Incorrect arguments were given to the function '{identifier}'. Received ({parameters:?})."),
+                Self::UnknownParameter(identifier, parameter) => format!("Function '{identifier}' has no parameter '{parameter}'."),
+                Self::DuplicateArgument(identifier, parameter) => format!("Parameter '{parameter}' of function '{identifier}' was given more than one value."),
                 Self::InvalidPredicate(predicate) => format!("Predicate must be a boolean. Received '{predicate:?}'."),
                 Self::DivisionByZero => String::from("Cannot divide by zero."),
+                Self::Overflow(identifier) => format!("Integer overflow in '{identifier}'."),
+                Self::InvalidStep => String::from("The step of a range cannot be zero."),
+                Self::OutsideLoop(keyword) => format!("'{keyword}' can only be used inside a loop."),
+                Self::UndefinedLabel(label) => format!("No enclosing loop is labeled '{label}'."),
                 Self::NotAList(value) => format!("{value:?} is not a list."),
+                Self::NotAMap(value) => format!("{value:?} is not a map."),
+                Self::NotAFunction(value) => format!("{value:?} is not a function."),
+                Self::NoMethod(value, identifier) => format!("{value:?} has no method '{identifier}'."),
+                Self::NotAStructure(value) => format!("{value:?} is not a struct."),
+                Self::UncapitalizedStructure(identifier) => format!("Struct '{identifier}' must have a capitalized name to be constructed."),
+                Self::NoField(value, field) => format!("{value:?} has no field '{field}'."),
+                Self::UnknownField(structure, field) => format!("Struct '{structure}' has no field '{field}'."),
+                Self::MissingField(structure, field) => format!("Field '{field}' of struct '{structure}' was not given a value."),
+                Self::DuplicateField(structure, field) => format!("Field '{field}' of struct '{structure}' was given more than one value."),
+                Self::NotAnEnumeration(value) => format!("{value:?} is not an enum."),
+                Self::UnknownVariant(enumeration, variant) => format!("Enum '{enumeration}' has no variant '{variant}'."),
+                Self::NoMatch(value) => format!("No arm of the match statement matches {value:?}."),
+                Self::PatternMismatch(value) => format!("{value:?} does not match the pattern."),
+                Self::LengthMismatch(expected, actual) => format!("Expected a list of {expected} elements to destructure, but it has {actual}."),
+                Self::TooFewElements(expected, actual) => format!("Expected a list of at least {expected} elements to destructure, but it has {actual}."),
+                Self::NotIndexable(value) => format!("{value:?} cannot be indexed."),
                 Self::NotAnIndex(value) => format!("{value:?} is not an index."),
+                Self::NotAKey(value) => format!("{value:?} cannot be used as a map key."),
+                Self::MissingKey(value) => format!("The map has no key {value:?}."),
                 Self::OutOufRange(value) => format!("Tried accessing an index that is out of range: {value:?}."),
+                Self::IndexLevel(level, error) => format!("Indexing failed at level {level}: {error}"),
+                Self::Thrown(value) => format!("Uncaught error: {value}"),
+                Self::InvalidImport(path, reason) => format!("Could not import '{path}': {reason}."),
+                Self::ImportCycle(cycle) => format!("Import cycle detected: {}.", cycle.join(" -> ")),
+                Self::NotExported(module, identifier) => format!("Module '{module}' has no top-level '{identifier}'."),
             }
         )
     }
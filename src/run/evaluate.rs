@@ -1,18 +1,21 @@
-use itertools::Itertools;
+use std::{collections::BTreeMap, rc::Rc};
 
-use crate::parse::{Atom, Expression, Identifier, Operand, Operation, Operator};
+use crate::parse::{
+    Argument, Atom, Expression, Identifier, Operand, Operation, Operator, StringPart,
+};
 
 use super::{
-    block::{self, BlockValue},
-    builtin,
+    block, builtin,
     context::Context,
-    function::Function,
-    utils::evaluate_values,
+    function::{Arity, Function},
+    key::Key,
+    method,
+    utils::{self, evaluate_values},
     value::Value,
     SplashRuntimeError,
 };
 
-pub type Result<'a> = std::result::Result<Option<Value>, SplashRuntimeError<'a>>;
+pub type Result<'a> = std::result::Result<Option<Value<'a>>, SplashRuntimeError<'a>>;
 
 fn evaluate_atom<'a>(atom: &Atom<'a>, context: &mut Context<'a>) -> Result<'a> {
     match atom {
@@ -21,96 +24,357 @@ fn evaluate_atom<'a>(atom: &Atom<'a>, context: &mut Context<'a>) -> Result<'a> {
     }
 }
 
+fn evaluate_operand<'a>(
+    operand: &Operand<'a>,
+    context: &mut Context<'a>,
+) -> std::result::Result<Value<'a>, SplashRuntimeError<'a>> {
+    match operand {
+        Operand::Operation(operation) => evaluate_operation(operation, context),
+        Operand::Expression(expression) => evaluate(expression, context),
+    }?
+    .ok_or(SplashRuntimeError::NoValue)
+}
+
 fn evaluate_operation<'a>(operation: &Operation<'a>, context: &mut Context<'a>) -> Result<'a> {
-    let values = operation
-        .operands()
-        .iter()
-        .map(|operand| match operand {
-            Operand::Operation(operation) => evaluate_operation(operation, context),
-            Operand::Expression(expression) => evaluate(expression, context),
-        })
-        .map_ok(|value| value.ok_or(SplashRuntimeError::NoValue))
-        .flatten_ok()
-        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let operands = operation.operands();
+    let left = evaluate_operand(&operands[0], context)?;
+
+    // The right operand of `&&`, `||` and `??` is only evaluated if the left one doesn't decide the result.
+    match (operation.operator(), &left) {
+        (Operator::And, Value::Boolean(false)) | (Operator::Or, Value::Boolean(true)) => {
+            return Ok(Some(left))
+        }
+        (Operator::Coalesce, value) if !matches!(value, Value::Nil) => return Ok(Some(left)),
+        _ => {}
+    }
 
     match operation.operator() {
-        Operator::Not => builtin::not(values[0].clone()),
-        Operator::Plus => builtin::plus(values[0].clone(), values[1].clone()),
-        Operator::Minus => builtin::minus(values[0].clone(), values[1].clone()),
-        Operator::Times => builtin::times(values[0].clone(), values[1].clone()),
-        Operator::Divide => builtin::divide(values[0].clone(), values[1].clone()),
-        Operator::Modulo => builtin::modulo(values[0].clone(), values[1].clone()),
-        Operator::Equal => builtin::equal(values[0].clone(), values[1].clone()),
-        Operator::NotEqual => builtin::not_equal(values[0].clone(), values[1].clone()),
-        Operator::GreaterThan => builtin::greater_than(values[0].clone(), values[1].clone()),
-        Operator::GreaterOrEqual => builtin::greater_or_equal(values[0].clone(), values[1].clone()),
-        Operator::LessThan => builtin::less_than(values[0].clone(), values[1].clone()),
-        Operator::LessOrEqual => builtin::less_or_equal(values[0].clone(), values[1].clone()),
-        Operator::And => builtin::and(values[0].clone(), values[1].clone()),
-        Operator::Or => builtin::or(values[0].clone(), values[1].clone()),
+        Operator::Not => builtin::not(left),
+        Operator::Negate => builtin::negate(left),
+        operator => binary(operator, left, evaluate_operand(&operands[1], context)?),
+    }
+}
+
+/// Applies a binary `operator` to its two operands.
+pub fn binary<'a>(operator: &Operator, left: Value<'a>, right: Value<'a>) -> Result<'a> {
+    match operator {
+        Operator::Not | Operator::Negate => unreachable!("unary operators take a single operand"),
+        Operator::Plus => builtin::plus(left, right),
+        Operator::Minus => builtin::minus(left, right),
+        Operator::Times => builtin::times(left, right),
+        Operator::Power => builtin::power(left, right),
+        Operator::Divide => builtin::divide(left, right),
+        Operator::Modulo => builtin::modulo(left, right),
+        Operator::Equal => builtin::equal(left, right),
+        Operator::NotEqual => builtin::not_equal(left, right),
+        Operator::GreaterThan => builtin::greater_than(left, right),
+        Operator::GreaterOrEqual => builtin::greater_or_equal(left, right),
+        Operator::LessThan => builtin::less_than(left, right),
+        Operator::LessOrEqual => builtin::less_or_equal(left, right),
+        Operator::And => builtin::and(left, right),
+        Operator::Or => builtin::or(left, right),
+        Operator::BitAnd => builtin::bit_and(left, right),
+        Operator::BitOr => builtin::bit_or(left, right),
+        Operator::BitXor => builtin::bit_xor(left, right),
+        Operator::ShiftLeft => builtin::shift_left(left, right),
+        Operator::ShiftRight => builtin::shift_right(left, right),
+        Operator::Range => builtin::exclusive_range(left, right),
+        Operator::RangeInclusive => builtin::inclusive_range(left, right),
+        Operator::Coalesce => builtin::coalesce(left, right),
     }
 }
 
+/// Evaluated arguments of a call: the positional ones in order, and the keyword ones along with the name of
+/// their parameter.
+type Arguments<'a> = (Vec<Value<'a>>, Vec<(Identifier<'a>, Value<'a>)>);
+
+fn evaluate_arguments<'a>(
+    arguments: &[Argument<'a>],
+    context: &mut Context<'a>,
+) -> std::result::Result<Arguments<'a>, SplashRuntimeError<'a>> {
+    let mut positional = Vec::new();
+    let mut keywords = Vec::new();
+
+    for argument in arguments {
+        match argument {
+            Argument::Positional(expression) => {
+                positional.push(evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?)
+            }
+            Argument::Keyword(name, expression) => keywords.push((
+                *name,
+                evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?,
+            )),
+        }
+    }
+
+    Ok((positional, keywords))
+}
+
 fn evaluate_function<'a>(
     identifier: &Identifier<'a>,
-    parameters: &[Expression<'a>],
+    arguments: &[Argument<'a>],
     context: &mut Context<'a>,
 ) -> Result<'a> {
-    let function = context.function(identifier).cloned()?;
-    let parameters = evaluate_values(parameters, context)?;
-
-    match function {
-        Function::BuiltIn(function) => function(&parameters),
-        Function::Custom(arguments, body) => {
-            if parameters.len() != arguments.len() {
-                return Err(SplashRuntimeError::InvalidSignature(
-                    identifier,
-                    arguments.len(),
-                    parameters.len(),
-                ));
+    let function = context.function(identifier)?;
+    let (positional, keywords) = evaluate_arguments(arguments, context)?;
+    call(identifier, &function, positional, keywords, context)
+}
+
+/// Calls the function an expression evaluates to, which has no name of its own to be reported by.
+fn evaluate_call<'a>(
+    function: &Expression<'a>,
+    arguments: &[Argument<'a>],
+    context: &mut Context<'a>,
+) -> Result<'a> {
+    let function = match evaluate(function, context)?.ok_or(SplashRuntimeError::NoValue)? {
+        Value::Function(function) => function,
+        value => return Err(SplashRuntimeError::NotAFunction(value)),
+    };
+
+    let (positional, keywords) = evaluate_arguments(arguments, context)?;
+    call("function", &function, positional, keywords, context)
+}
+
+fn evaluate_method<'a>(
+    receiver: &Expression<'a>,
+    identifier: &Identifier<'a>,
+    arguments: &[Argument<'a>],
+    context: &mut Context<'a>,
+) -> Result<'a> {
+    let receiver = evaluate(receiver, context)?.ok_or(SplashRuntimeError::NoValue)?;
+
+    match receiver {
+        Value::Enumeration(enumeration) => {
+            let variant = enumeration.variant(identifier)?;
+            let (values, keywords) = evaluate_arguments(arguments, context)?;
+            if let Some((name, _)) = keywords.first() {
+                return Err(SplashRuntimeError::UnknownParameter(identifier, name));
             }
 
-            context.child(|context| {
-                for (i, parameter) in parameters.into_iter().enumerate() {
-                    context.initialize_variable(arguments[i], parameter);
-                }
+            let values = enumeration.instantiate(variant, values)?;
+            return Ok(Some(Value::Variant(enumeration, variant, values)));
+        }
+        Value::Module(module) => {
+            let function = match module.member(identifier)? {
+                Value::Function(function) => function,
+                value => return Err(SplashRuntimeError::NotAFunction(value)),
+            };
 
-                match block::run(&body, context)? {
-                    BlockValue::Return(value) => Ok(value),
-                    BlockValue::None => Ok(None),
+            let (positional, keywords) = evaluate_arguments(arguments, context)?;
+            return call(identifier, &function, positional, keywords, context);
+        }
+        // Functions held by the fields of an instance are called without the instance.
+        Value::Instance(ref structure, ref values) => {
+            if let Ok(position) = structure.field(identifier) {
+                if let Value::Function(function) = &values[position] {
+                    let function = Rc::clone(function);
+                    let (positional, keywords) = evaluate_arguments(arguments, context)?;
+                    return call(identifier, &function, positional, keywords, context);
                 }
-            })
+            }
         }
+        _ => {}
     }
+
+    // Built-in methods of the receiver's type take precedence over functions in scope.
+    let function = match method::method(&receiver, identifier) {
+        Some(method) => Rc::new(method),
+        None => context.function(identifier).map_err(|error| match error {
+            SplashRuntimeError::NotDefined(_) | SplashRuntimeError::NotAFunction(_) => {
+                SplashRuntimeError::NoMethod(receiver.clone(), identifier)
+            }
+            error => error,
+        })?,
+    };
+
+    let (mut positional, keywords) = evaluate_arguments(arguments, context)?;
+    positional.insert(0, receiver);
+
+    call(identifier, &function, positional, keywords, context)
+}
+
+fn evaluate_field<'a>(
+    instance: &Expression<'a>,
+    field: &Identifier<'a>,
+    context: &mut Context<'a>,
+) -> Result<'a> {
+    let instance = evaluate(instance, context)?.ok_or(SplashRuntimeError::NoValue)?;
+    utils::field(instance, field).map(Some)
+}
+
+fn evaluate_construction<'a>(
+    identifier: &Identifier<'a>,
+    fields: &[(Identifier<'a>, Expression<'a>)],
+    context: &mut Context<'a>,
+) -> Result<'a> {
+    let structure = match context.variable(identifier)? {
+        Value::Structure(structure) => structure,
+        value => return Err(SplashRuntimeError::NotAStructure(value)),
+    };
+
+    let mut values = Vec::with_capacity(fields.len());
+    for (field, expression) in fields {
+        let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+        values.push((*field, value));
+    }
+
+    let values = structure.instantiate(values)?;
+    Ok(Some(Value::Instance(structure, values)))
+}
+
+/// Calls `function` with already evaluated positional and keyword arguments, `identifier` being the name it
+/// was called by. Positional arguments fill the parameters in order, the extra ones being collected by the
+/// variadic parameter if there is one, and keyword arguments fill the parameters of the same name.
+pub fn call<'a>(
+    identifier: Identifier<'a>,
+    function: &Function<'a>,
+    positional: Vec<Value<'a>>,
+    keywords: Vec<(Identifier<'a>, Value<'a>)>,
+    context: &mut Context<'a>,
+) -> Result<'a> {
+    let (parameters, body, environment) = match function {
+        Function::BuiltIn(function) => {
+            if let Some((name, _)) = keywords.first() {
+                return Err(SplashRuntimeError::UnknownParameter(identifier, name));
+            }
+
+            return Ok(Some(function(&positional)?.unwrap_or(Value::Nil)));
+        }
+        Function::HigherOrder(function) => {
+            if let Some((name, _)) = keywords.first() {
+                return Err(SplashRuntimeError::UnknownParameter(identifier, name));
+            }
+
+            return Ok(Some(function(&positional, context)?.unwrap_or(Value::Nil)));
+        }
+        Function::Custom(parameters, body, environment) => (parameters, body, environment),
+    };
+
+    let arity = Arity::of(parameters);
+    let count = positional.len() + keywords.len();
+
+    let (fixed, variadic) = match parameters.split_last() {
+        Some((last, fixed)) if last.variadic() => (fixed, Some(last)),
+        _ => (&parameters[..], None),
+    };
+
+    let mut positional = positional.into_iter();
+    let mut values: Vec<_> = positional.by_ref().take(fixed.len()).map(Some).collect();
+    values.resize(fixed.len(), None);
+
+    let extra: Vec<_> = positional.collect();
+    if !extra.is_empty() && variadic.is_none() {
+        return Err(SplashRuntimeError::InvalidSignature(
+            identifier, arity, count,
+        ));
+    }
+
+    for (name, value) in keywords {
+        let position = fixed
+            .iter()
+            .position(|parameter| parameter.name() == name)
+            .ok_or(SplashRuntimeError::UnknownParameter(identifier, name))?;
+
+        if values[position].is_some() {
+            return Err(SplashRuntimeError::DuplicateArgument(identifier, name));
+        }
+        values[position] = Some(value);
+    }
+
+    let missing = fixed
+        .iter()
+        .zip(&values)
+        .any(|(parameter, value)| value.is_none() && parameter.default().is_none());
+    if missing {
+        return Err(SplashRuntimeError::InvalidSignature(
+            identifier, arity, count,
+        ));
+    }
+
+    // The body runs on top of the scope the function was defined in rather than the caller's, so that it
+    // can neither see nor overwrite the caller's variables.
+    context.child_of(Rc::clone(environment), |context| {
+        // Default values are evaluated at each call, and can refer to the parameters before them.
+        for (parameter, value) in fixed.iter().zip(values) {
+            let value = match (value, parameter.default()) {
+                (Some(value), _) => value,
+                (None, Some(default)) => {
+                    evaluate(default, context)?.ok_or(SplashRuntimeError::NoValue)?
+                }
+                (None, None) => unreachable!("missing arguments were checked before the call"),
+            };
+            context.initialize_variable(parameter.name(), value);
+        }
+
+        if let Some(variadic) = variadic {
+            context.initialize_variable(variadic.name(), Value::List(extra));
+        }
+
+        // Falling off the end of the body, or a bare `return`, yields nil.
+        Ok(Some(
+            block::run(body, context)?.returned()?.unwrap_or(Value::Nil),
+        ))
+    })
 }
 
 fn evaluate_list<'a>(elements: &[Expression<'a>], context: &mut Context<'a>) -> Result<'a> {
     Ok(Some(Value::List(evaluate_values(elements, context)?)))
 }
 
+fn evaluate_map<'a>(
+    entries: &[(Expression<'a>, Expression<'a>)],
+    context: &mut Context<'a>,
+) -> Result<'a> {
+    let mut map = BTreeMap::new();
+
+    for (key, value) in entries {
+        let key = evaluate(key, context)?.ok_or(SplashRuntimeError::NoValue)?;
+        let value = evaluate(value, context)?.ok_or(SplashRuntimeError::NoValue)?;
+        map.insert(Key::try_from(key)?, value);
+    }
+
+    Ok(Some(Value::Map(map)))
+}
+
 fn evaluate_index<'a>(
-    identifier: &Identifier<'a>,
+    collection: &Expression<'a>,
     index: &Expression<'a>,
     context: &mut Context<'a>,
 ) -> Result<'a> {
-    let list = context.variable(identifier)?;
-    let index = evaluate(index, context)?.ok_or(SplashRuntimeError::NoValue)?;
+    // The whole chain of indices is handled at once, so that errors can tell which level failed.
+    let mut indices = vec![index];
+    let mut collection = collection;
+    while let Expression::Index(inner, index) = collection {
+        indices.push(index);
+        collection = inner;
+    }
 
-    let list = match list {
-        Value::List(list) => list,
-        value => return Err(SplashRuntimeError::NotAList(value)),
-    };
+    let depth = indices.len();
+    let mut value = evaluate(collection, context)?.ok_or(SplashRuntimeError::NoValue)?;
 
-    let index = match index {
-        Value::Number(number) => number as usize,
-        value => return Err(SplashRuntimeError::NotAnIndex(value)),
-    };
+    for (level, index) in indices.into_iter().rev().enumerate() {
+        let index = evaluate(index, context)?.ok_or(SplashRuntimeError::NoValue)?;
+        value =
+            utils::index(value, index).map_err(|error| utils::at_level(error, level + 1, depth))?;
+    }
 
-    Ok(Some(
-        list.get(index)
-            .ok_or(SplashRuntimeError::OutOufRange(Value::Number(index as f64)))?
-            .clone(),
-    ))
+    Ok(Some(value))
+}
+
+fn evaluate_interpolation<'a>(parts: &[StringPart<'a>], context: &mut Context<'a>) -> Result<'a> {
+    let mut string = String::new();
+
+    for part in parts {
+        match part {
+            StringPart::Literal(literal) => string.push_str(literal),
+            StringPart::Expression(expression) => {
+                let value = evaluate(expression, context)?.ok_or(SplashRuntimeError::NoValue)?;
+                string.push_str(&value.to_string());
+            }
+        }
+    }
+
+    Ok(Some(Value::String(string)))
 }
 
 pub fn evaluate<'a>(expression: &Expression<'a>, context: &mut Context<'a>) -> Result<'a> {
@@ -120,7 +384,20 @@ pub fn evaluate<'a>(expression: &Expression<'a>, context: &mut Context<'a>) -> R
         Expression::Function(identifier, parameters) => {
             evaluate_function(identifier, parameters, context)
         }
+        Expression::Call(function, arguments) => evaluate_call(function, arguments, context),
         Expression::List(elements) => evaluate_list(elements, context),
-        Expression::Index(identifier, index) => evaluate_index(identifier, index, context),
+        Expression::Map(entries) => evaluate_map(entries, context),
+        Expression::Index(collection, index) => evaluate_index(collection, index, context),
+        Expression::Method(receiver, identifier, parameters) => {
+            evaluate_method(receiver, identifier, parameters, context)
+        }
+        Expression::Field(instance, field) => evaluate_field(instance, field, context),
+        Expression::Construction(identifier, fields) => {
+            evaluate_construction(identifier, fields, context)
+        }
+        Expression::Lambda(arguments, body) => Ok(Some(Value::Function(Rc::new(
+            Function::Custom(arguments.clone(), body.clone(), context.environment()),
+        )))),
+        Expression::Interpolation(parts) => evaluate_interpolation(parts, context),
     }
 }
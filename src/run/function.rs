@@ -1,9 +1,122 @@
-use crate::parse::{Block, Identifier};
+use std::fmt::{Debug, Display};
 
-use super::{evaluate::Result, value::Value};
+use crate::parse::{Block, Parameter};
 
-#[derive(Clone, Debug)]
+use super::{
+    context::{Context, Environment},
+    evaluate::Result,
+    value::Value,
+};
+
+pub type BuiltIn<'a> = fn(&[Value<'a>]) -> Result<'a>;
+
+/// A built-in function that takes functions as arguments, which it calls through the context.
+pub type HigherOrder<'a> = fn(&[Value<'a>], &mut Context<'a>) -> Result<'a>;
+
+#[derive(Clone)]
 pub enum Function<'a> {
-    BuiltIn(fn(&[Value]) -> Result<'a>),
-    Custom(Vec<Identifier<'a>>, Block<'a>),
+    BuiltIn(BuiltIn<'a>),
+    HigherOrder(HigherOrder<'a>),
+    /// A user-defined function, along with the environment it was defined in. The environment is held strongly,
+    /// since closures returned from a function must keep its scope alive after it returns. A function stored in
+    /// the scope it was defined in, as every named function is, forms a reference cycle with it, so that scope
+    /// is never freed. This is a deliberate trade-off: programs are short-lived, and breaking the cycle would
+    /// need a garbage collector.
+    Custom(Vec<Parameter<'a>>, Block<'a>, Environment<'a>),
+}
+
+/// Functions are compared by identity: two values are equal only if they refer to the same function.
+impl PartialEq for Function<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// The environment of a custom function may contain the closure itself, so it is left out.
+impl Debug for Function<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BuiltIn(_) => f.write_str("BuiltIn"),
+            Self::HigherOrder(_) => f.write_str("HigherOrder"),
+            Self::Custom(parameters, _, _) => f.debug_tuple("Custom").field(parameters).finish(),
+        }
+    }
+}
+
+impl Display for Function<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BuiltIn(_) | Self::HigherOrder(_) => write!(f, "<built-in function>"),
+            Self::Custom(parameters, _, _) => write!(
+                f,
+                "<function({})>",
+                parameters
+                    .iter()
+                    .map(|parameter| if parameter.variadic() {
+                        format!("..{}", parameter.name())
+                    } else {
+                        parameter.name().to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// The numbers of arguments a function accepts, from a minimum up to an optional maximum.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Arity {
+    minimum: usize,
+    maximum: Option<usize>,
+}
+
+impl Arity {
+    pub fn exactly(count: usize) -> Self {
+        Self::between(count, count)
+    }
+
+    pub fn between(minimum: usize, maximum: usize) -> Self {
+        Self {
+            minimum,
+            maximum: Some(maximum),
+        }
+    }
+
+    pub fn at_least(minimum: usize) -> Self {
+        Self {
+            minimum,
+            maximum: None,
+        }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.minimum && self.maximum.is_none_or(|maximum| count <= maximum)
+    }
+
+    /// Parameters with a default value are optional, and a variadic parameter lifts the maximum.
+    pub fn of(parameters: &[Parameter]) -> Self {
+        let minimum = parameters
+            .iter()
+            .filter(|parameter| parameter.default().is_none() && !parameter.variadic())
+            .count();
+
+        if parameters.iter().any(Parameter::variadic) {
+            Self::at_least(minimum)
+        } else {
+            Self::between(minimum, parameters.len())
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize| if count == 1 { "argument" } else { "arguments" };
+
+        match self.maximum {
+            Some(maximum) if maximum == self.minimum => write!(f, "{maximum} {}", plural(maximum)),
+            Some(maximum) => write!(f, "between {} and {maximum} arguments", self.minimum),
+            None => write!(f, "at least {} {}", self.minimum, plural(self.minimum)),
+        }
+    }
 }
@@ -0,0 +1,77 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use super::{value::Value, SplashRuntimeError};
+
+/// A value that can be used as a key in a map.
+#[derive(Clone, Debug)]
+pub enum Key {
+    Integer(i64),
+    Number(f64),
+    String(String),
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Numbers are ordered before strings. Integers and floats are ordered together by value.
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Integer(left), Self::Integer(right)) => left.cmp(right),
+            (Self::Integer(left), Self::Number(right)) => (*left as f64).total_cmp(right),
+            (Self::Number(left), Self::Integer(right)) => left.total_cmp(&(*right as f64)),
+            (Self::Number(left), Self::Number(right)) => left.total_cmp(right),
+            (Self::String(left), Self::String(right)) => left.cmp(right),
+            (Self::Integer(_) | Self::Number(_), Self::String(_)) => Ordering::Less,
+            (Self::String(_), Self::Integer(_) | Self::Number(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl<'a> TryFrom<Value<'a>> for Key {
+    type Error = SplashRuntimeError<'a>;
+
+    /// Floats holding an integral value are stored as integers, so that `1` and `1.0` are the same key.
+    fn try_from(value: Value<'a>) -> Result<Self, Self::Error> {
+        match value {
+            Value::Integer(integer) => Ok(Self::Integer(integer)),
+            Value::Number(number) if number.fract() == 0.0 && number.abs() < i64::MAX as f64 => {
+                Ok(Self::Integer(number as i64))
+            }
+            Value::Number(number) => Ok(Self::Number(number)),
+            Value::String(string) => Ok(Self::String(string)),
+            value => Err(SplashRuntimeError::NotAKey(value)),
+        }
+    }
+}
+
+impl From<Key> for Value<'_> {
+    fn from(value: Key) -> Self {
+        match value {
+            Key::Integer(integer) => Value::Integer(integer),
+            Key::Number(number) => Value::Number(number),
+            Key::String(string) => Value::String(string),
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(integer) => integer.fmt(f),
+            Self::Number(number) => number.fmt(f),
+            Self::String(string) => string.fmt(f),
+        }
+    }
+}
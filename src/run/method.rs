@@ -0,0 +1,109 @@
+use crate::parse::Identifier;
+
+use super::{
+    builtin,
+    function::{BuiltIn, Function, HigherOrder},
+    value::Value,
+};
+
+/// Returns the built-in method `name` of `receiver`, which is called with `receiver` as its first argument.
+pub fn method<'a>(receiver: &Value<'a>, name: Identifier) -> Option<Function<'a>> {
+    if let Some(method) = higher_order(receiver, name) {
+        return Some(Function::HigherOrder(method));
+    }
+
+    let method = match (receiver, name) {
+        (Value::List(_), "length") => builtin::length as BuiltIn,
+        (Value::List(_), "push") => builtin::push as BuiltIn,
+        (Value::List(_), "pop") => builtin::pop as BuiltIn,
+        (Value::List(_), "contains") => builtin::list::contains as BuiltIn,
+        (Value::List(_), "index_of") => builtin::list::index_of as BuiltIn,
+        (Value::List(_), "insert") => builtin::list::insert as BuiltIn,
+        (Value::List(_), "remove_at") => builtin::list::remove_at as BuiltIn,
+        (Value::List(_), "remove_value") => builtin::list::remove_value as BuiltIn,
+        (Value::List(_), "concat") => builtin::list::concat as BuiltIn,
+        (Value::List(_), "reverse") => builtin::list::reverse as BuiltIn,
+        (Value::List(_), "slice") => builtin::list::slice as BuiltIn,
+        (Value::List(_), "sort") => builtin::list::sort as BuiltIn,
+        (Value::List(_), "unique") => builtin::list::unique as BuiltIn,
+        (Value::List(_), "flatten") => builtin::list::flatten as BuiltIn,
+        (Value::List(_), "zip") => builtin::list::zip as BuiltIn,
+        (Value::List(_), "enumerate") => builtin::list::enumerate as BuiltIn,
+        (Value::List(_), "min") => builtin::math::min as BuiltIn,
+        (Value::List(_), "max") => builtin::math::max as BuiltIn,
+        (Value::List(_), "sum") => builtin::math::sum as BuiltIn,
+        (Value::List(_), "join") => builtin::string::join as BuiltIn,
+
+        (Value::String(_), "length") => builtin::length as BuiltIn,
+        (Value::String(_), "split") => builtin::string::split as BuiltIn,
+        (Value::String(_), "trim") => builtin::string::trim as BuiltIn,
+        (Value::String(_), "trim_start") => builtin::string::trim_start as BuiltIn,
+        (Value::String(_), "trim_end") => builtin::string::trim_end as BuiltIn,
+        (Value::String(_), "replace") => builtin::string::replace as BuiltIn,
+        (Value::String(_), "starts_with") => builtin::string::starts_with as BuiltIn,
+        (Value::String(_), "ends_with") => builtin::string::ends_with as BuiltIn,
+        (Value::String(_), "find") => builtin::string::find as BuiltIn,
+        (Value::String(_), "upper") => builtin::string::upper as BuiltIn,
+        (Value::String(_), "lower") => builtin::string::lower as BuiltIn,
+        (Value::String(_), "repeat") => builtin::string::repeat as BuiltIn,
+        (Value::String(_), "pad_left") => builtin::string::pad_left as BuiltIn,
+        (Value::String(_), "pad_right") => builtin::string::pad_right as BuiltIn,
+        (Value::String(_), "chars") => builtin::string::chars as BuiltIn,
+        (Value::String(_), "substring") => builtin::string::substring as BuiltIn,
+
+        (Value::Map(_), "length") => builtin::length as BuiltIn,
+        (Value::Map(_), "keys") => builtin::keys as BuiltIn,
+        (Value::Map(_), "values") => builtin::values as BuiltIn,
+        (Value::Map(_), "entries") => builtin::entries as BuiltIn,
+        (Value::Map(_), "has") => builtin::has as BuiltIn,
+        (Value::Map(_), "remove") => builtin::remove as BuiltIn,
+
+        (Value::Range(_), "length") => builtin::length as BuiltIn,
+        (Value::Range(_), "list") => builtin::list as BuiltIn,
+        (Value::Range(_), "sum") => builtin::math::sum as BuiltIn,
+        (Value::Range(_), "contains") => builtin::list::contains as BuiltIn,
+
+        (Value::Integer(_), "range") => builtin::range as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "is_nan") => builtin::is_nan as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "is_finite") => builtin::is_finite as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "is_integer") => builtin::is_integer as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "abs") => builtin::math::abs as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "floor") => builtin::math::floor as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "ceil") => builtin::math::ceil as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "round") => builtin::math::round as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "trunc") => builtin::math::trunc as BuiltIn,
+        (Value::Integer(_) | Value::Number(_), "sqrt") => builtin::math::sqrt as BuiltIn,
+
+        (_, "string") => builtin::string as BuiltIn,
+        (_, "print") => builtin::print as BuiltIn,
+
+        _ => return None,
+    };
+
+    Some(Function::BuiltIn(method))
+}
+
+fn higher_order<'a>(receiver: &Value<'a>, name: Identifier) -> Option<HigherOrder<'a>> {
+    let method = match (receiver, name) {
+        (Value::List(_) | Value::Range(_), "map") => builtin::higher_order::map as HigherOrder,
+        (Value::List(_) | Value::Range(_), "filter") => {
+            builtin::higher_order::filter as HigherOrder
+        }
+        (Value::List(_) | Value::Range(_), "reduce") => {
+            builtin::higher_order::reduce as HigherOrder
+        }
+        (Value::List(_) | Value::Range(_), "sort_by") => {
+            builtin::higher_order::sort_by as HigherOrder
+        }
+        (Value::List(_) | Value::Range(_), "find") => builtin::higher_order::find as HigherOrder,
+        (Value::List(_) | Value::Range(_), "any") => builtin::higher_order::any as HigherOrder,
+        (Value::List(_) | Value::Range(_), "all") => builtin::higher_order::all as HigherOrder,
+        (Value::List(_) | Value::Range(_), "group_by") => {
+            builtin::higher_order::group_by as HigherOrder
+        }
+
+        _ => return None,
+    };
+
+    Some(method)
+}
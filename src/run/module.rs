@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use nom::Parser;
+
+use crate::{
+    parse::{Identifier, SplashParser},
+    utils::locate_error,
+};
+
+use super::{
+    block,
+    check::check,
+    context::{Context, Environment},
+    value::Value,
+    SplashRuntimeError,
+};
+
+/// The top-level scope of an imported file.
+pub struct Module<'a> {
+    name: Identifier<'a>,
+    scope: Environment<'a>,
+}
+
+impl<'a> Module<'a> {
+    pub fn name(&self) -> Identifier<'a> {
+        self.name
+    }
+
+    /// Returns the top-level variable or function `identifier` of the module.
+    pub fn member(&self, identifier: Identifier<'a>) -> Result<Value<'a>, SplashRuntimeError<'a>> {
+        self.scope
+            .borrow()
+            .get(identifier)
+            .ok_or(SplashRuntimeError::NotExported(self.name, identifier))
+    }
+}
+
+/// Modules are compared by identity, as each file is only loaded once.
+impl PartialEq for Module<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for Module<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Module").field(&self.name).finish()
+    }
+}
+
+impl Display for Module<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
+
+/// The modules of a program, shared by the contexts of all its files.
+#[derive(Default)]
+pub struct Modules<'a> {
+    loaded: HashMap<PathBuf, Rc<Module<'a>>>,
+    /// The files being imported, from the outermost to the innermost, to detect cycles.
+    loading: Vec<PathBuf>,
+}
+
+impl Modules<'_> {
+    /// Marks the file at `path` as being run, so that importing it back is detected as a cycle.
+    pub fn enter(&mut self, path: &Path) {
+        self.loading
+            .push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    }
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+/// Imports the file at `path`, relative to the file being run by `context`. Each file is parsed and run once,
+/// the following imports returning the same module.
+pub fn import<'a>(
+    path: &'a str,
+    context: &mut Context<'a>,
+) -> Result<Rc<Module<'a>>, SplashRuntimeError<'a>> {
+    let resolved = match context.path().and_then(Path::parent) {
+        Some(directory) => directory.join(path),
+        None => PathBuf::from(path),
+    };
+
+    let resolved = resolved
+        .canonicalize()
+        .map_err(|error| SplashRuntimeError::InvalidImport(path, error.to_string()))?;
+
+    let modules = context.modules();
+
+    if let Some(module) = modules.borrow().loaded.get(&resolved) {
+        return Ok(Rc::clone(module));
+    }
+
+    if let Some(start) = modules.borrow().loading.iter().position(|p| *p == resolved) {
+        let mut cycle: Vec<_> = modules.borrow().loading[start..]
+            .iter()
+            .map(|path| display(path))
+            .collect();
+        cycle.push(display(&resolved));
+
+        return Err(SplashRuntimeError::ImportCycle(cycle));
+    }
+
+    let input = read_to_string(&resolved)
+        .map_err(|error| SplashRuntimeError::InvalidImport(path, error.to_string()))?;
+
+    // The syntax tree borrows from its source for as long as the program runs, and modules are never
+    // unloaded, so the source is leaked.
+    let input: &'a str = Box::leak(format!("{{{input}}}").into_boxed_str());
+
+    let program = match SplashParser.parse(input) {
+        Ok((_, program)) => program,
+        Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+            let reason = match locate_error(input, &error) {
+                Some((line, column)) => format!("parse error at line {line}, column {column}"),
+                None => format!("unexpected error: {error:?}"),
+            };
+
+            return Err(SplashRuntimeError::InvalidImport(path, reason));
+        }
+        // Some parsers are streaming, and ask for more input when the file ends inside a string.
+        Err(nom::Err::Incomplete(_)) => {
+            return Err(SplashRuntimeError::InvalidImport(
+                path,
+                String::from("unexpected end of file"),
+            ));
+        }
+    };
+
+    check(&program)?;
+    modules.borrow_mut().enter(&resolved);
+
+    let mut module_context = Context::module(resolved.clone(), Rc::clone(&modules));
+    let result = block::run(&program, &mut module_context).and_then(|value| value.returned());
+
+    modules.borrow_mut().loading.pop();
+    result?;
+
+    let name = Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path);
+
+    let module = Rc::new(Module {
+        name,
+        scope: module_context.environment(),
+    });
+
+    modules
+        .borrow_mut()
+        .loaded
+        .insert(resolved, Rc::clone(&module));
+
+    Ok(module)
+}
@@ -0,0 +1,207 @@
+use std::rc::Rc;
+
+use crate::parse::{Identifier, Pattern};
+
+use super::{context::Context, function::Arity, key::Key, value::Value, SplashRuntimeError};
+
+/// Checks whether `value` matches `pattern`, collecting the variables it binds into `bindings`.
+pub fn matches<'a>(
+    pattern: &Pattern<'a>,
+    value: &Value<'a>,
+    context: &Context<'a>,
+    bindings: &mut Vec<(Identifier<'a>, Value<'a>)>,
+) -> Result<bool, SplashRuntimeError<'a>> {
+    match (pattern, value) {
+        (Pattern::Wildcard, _) => Ok(true),
+        (Pattern::Binding(identifier), value) => {
+            bindings.push((identifier, value.clone()));
+            Ok(true)
+        }
+        (Pattern::Literal(literal), value) => Ok(Value::from(literal.clone()) == *value),
+        (Pattern::List(patterns), Value::List(values)) => match split(patterns, values) {
+            Some((elements, rest)) => {
+                if let Some((Some(identifier), rest)) = rest {
+                    bindings.push((identifier, Value::List(rest.to_vec())));
+                }
+
+                for (pattern, value) in elements {
+                    if !matches(pattern, value, context, bindings)? {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+            None => Ok(false),
+        },
+        (Pattern::Rest(_), _) => unreachable!("rest elements only appear in list patterns"),
+        (Pattern::Variant(enumeration, variant, patterns), value) => {
+            let enumeration = match context.variable(enumeration)? {
+                Value::Enumeration(enumeration) => enumeration,
+                value => return Err(SplashRuntimeError::NotAnEnumeration(value)),
+            };
+
+            let position = enumeration.variant(variant)?;
+
+            match value {
+                Value::Variant(other, other_position, values)
+                    if Rc::ptr_eq(&enumeration, other) && position == *other_position =>
+                {
+                    if patterns.len() != values.len() {
+                        return Err(SplashRuntimeError::InvalidSignature(
+                            variant,
+                            Arity::exactly(values.len()),
+                            patterns.len(),
+                        ));
+                    }
+
+                    all_match(patterns, values, context, bindings)
+                }
+                _ => Ok(false),
+            }
+        }
+        (Pattern::Structure(structure, fields), value) => {
+            let structure = match context.variable(structure)? {
+                Value::Structure(structure) => structure,
+                value => return Err(SplashRuntimeError::NotAStructure(value)),
+            };
+
+            match value {
+                Value::Instance(other, values) if Rc::ptr_eq(&structure, other) => {
+                    for (field, pattern) in fields {
+                        let position = structure.field(field)?;
+                        if !matches(pattern, &values[position], context, bindings)? {
+                            return Ok(false);
+                        }
+                    }
+
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+        (Pattern::Map(entries), Value::Map(map)) => {
+            for (key, pattern) in entries {
+                let key = Key::try_from(Value::from(key.clone()))?;
+                match map.get(&key) {
+                    Some(value) if matches(pattern, value, context, bindings)? => {}
+                    _ => return Ok(false),
+                }
+            }
+
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+fn all_match<'a>(
+    patterns: &[Pattern<'a>],
+    values: &[Value<'a>],
+    context: &Context<'a>,
+    bindings: &mut Vec<(Identifier<'a>, Value<'a>)>,
+) -> Result<bool, SplashRuntimeError<'a>> {
+    for (pattern, value) in patterns.iter().zip(values) {
+        if !matches(pattern, value, context, bindings)? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// The patterns of a list pattern paired with the elements they match, and the rest element's name with the
+/// elements it collects.
+type Split<'p, 'a> = (
+    Vec<(&'p Pattern<'a>, &'p Value<'a>)>,
+    Option<(Option<Identifier<'a>>, &'p [Value<'a>])>,
+);
+
+/// Pairs the patterns of a list pattern with the `values` of a list, or returns `None` if the list doesn't
+/// have as many elements as the pattern requires.
+fn split<'p, 'a>(patterns: &'p [Pattern<'a>], values: &'p [Value<'a>]) -> Option<Split<'p, 'a>> {
+    let rest = patterns
+        .iter()
+        .position(|pattern| matches!(pattern, Pattern::Rest(_)));
+
+    let Some(rest) = rest else {
+        return (patterns.len() == values.len())
+            .then(|| (patterns.iter().zip(values).collect(), None));
+    };
+
+    let after = patterns.len() - rest - 1;
+    if values.len() < rest + after {
+        return None;
+    }
+
+    let identifier = match &patterns[rest] {
+        Pattern::Rest(identifier) => *identifier,
+        _ => unreachable!("the pattern at this position is the rest element"),
+    };
+
+    let end = values.len() - after;
+    let elements = patterns[..rest]
+        .iter()
+        .zip(&values[..rest])
+        .chain(patterns[rest + 1..].iter().zip(&values[end..]))
+        .collect();
+
+    Some((elements, Some((identifier, &values[rest..end]))))
+}
+
+/// Binds the variables of a pattern that `value` must match, such as the one of a `let` statement, failing
+/// with an error that explains the mismatch otherwise.
+pub fn destructure<'a>(
+    pattern: &Pattern<'a>,
+    value: Value<'a>,
+    context: &Context<'a>,
+) -> Result<Vec<(Identifier<'a>, Value<'a>)>, SplashRuntimeError<'a>> {
+    // Plain variables are the common case, which doesn't need to clone the value.
+    if let Pattern::Binding(identifier) = pattern {
+        return Ok(vec![(identifier, value)]);
+    }
+
+    let mut bindings = Vec::new();
+    if matches(pattern, &value, context, &mut bindings)? {
+        return Ok(bindings);
+    }
+
+    Err(mismatch(pattern, value, context)?)
+}
+
+/// Finds the innermost part of `value` that doesn't match `pattern`, reporting lists of the wrong length.
+fn mismatch<'a>(
+    pattern: &Pattern<'a>,
+    value: Value<'a>,
+    context: &Context<'a>,
+) -> Result<SplashRuntimeError<'a>, SplashRuntimeError<'a>> {
+    if let (Pattern::List(patterns), Value::List(values)) = (pattern, &value) {
+        let has_rest = patterns
+            .iter()
+            .any(|pattern| matches!(pattern, Pattern::Rest(_)));
+
+        match split(patterns, values) {
+            None if has_rest => {
+                return Ok(SplashRuntimeError::TooFewElements(
+                    patterns.len() - 1,
+                    values.len(),
+                ))
+            }
+            None => {
+                return Ok(SplashRuntimeError::LengthMismatch(
+                    patterns.len(),
+                    values.len(),
+                ))
+            }
+            Some((elements, _)) => {
+                for (pattern, value) in elements {
+                    if !matches(pattern, value, context, &mut Vec::new())? {
+                        return mismatch(pattern, value.clone(), context);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(SplashRuntimeError::PatternMismatch(value))
+}
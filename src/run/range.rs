@@ -0,0 +1,84 @@
+use std::{fmt::Display, iter::successors};
+
+use super::SplashRuntimeError;
+
+/// A lazy sequence of integers from `start` up to, but excluding, `stop`, separated by `step`. Ranges with a
+/// negative step count down. They can be read like the list of their elements, but not modified in place.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Range {
+    start: i64,
+    stop: i64,
+    step: i64,
+}
+
+impl Range {
+    pub fn new<'a>(start: i64, stop: i64, step: i64) -> Result<Self, SplashRuntimeError<'a>> {
+        if step == 0 {
+            return Err(SplashRuntimeError::InvalidStep);
+        }
+
+        Ok(Self { start, stop, step })
+    }
+
+    /// A range from `start` up to and including `stop`, by steps of one.
+    pub fn inclusive<'a>(start: i64, stop: i64) -> Result<Self, SplashRuntimeError<'a>> {
+        let stop = stop
+            .checked_add(1)
+            .ok_or(SplashRuntimeError::Overflow("inclusive_range"))?;
+        Self::new(start, stop, 1)
+    }
+
+    /// Produces the integers of the range one at a time, without storing them.
+    pub fn iter(self) -> impl Iterator<Item = i64> {
+        let Self { start, stop, step } = self;
+
+        successors(Some(start), move |i| i.checked_add(step)).take_while(move |&i| {
+            if step > 0 {
+                i < stop
+            } else {
+                i > stop
+            }
+        })
+    }
+
+    /// The number of elements, which saturates at `usize::MAX` on platforms where it doesn't fit.
+    pub fn len(&self) -> usize {
+        let (start, stop, step) = (
+            i128::from(self.start),
+            i128::from(self.stop),
+            i128::from(self.step),
+        );
+
+        let distance = if step > 0 { stop - start } else { start - stop };
+        if distance <= 0 {
+            0
+        } else {
+            usize::try_from((distance - 1) / step.abs() + 1).unwrap_or(usize::MAX)
+        }
+    }
+
+    /// The element at `index`, if the range is long enough.
+    pub fn get(&self, index: usize) -> Option<i64> {
+        if index >= self.len() {
+            return None;
+        }
+
+        // Elements of the range lie between its bounds, so they fit.
+        let element = i128::from(self.start) + index as i128 * i128::from(self.step);
+        i64::try_from(element).ok()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.step == 1 {
+            write!(f, "{}..{}", self.start, self.stop)
+        } else {
+            write!(f, "range({}, {}, {})", self.start, self.stop, self.step)
+        }
+    }
+}
@@ -1,21 +1,121 @@
+use std::path::PathBuf;
+
 use crate::parse::Program;
 
-use super::{block, context::Context, SplashRuntimeError};
+use super::{block, check::check, context::Context, SplashRuntimeError};
 
 pub struct Runtime<'a> {
     program: Program<'a>,
+    path: Option<PathBuf>,
 }
 
 impl<'a> Runtime<'a> {
     #[must_use]
     pub fn new(program: Program<'a>) -> Self {
-        Self { program }
+        Self {
+            program,
+            path: None,
+        }
+    }
+
+    /// Sets the file the program was read from, which imports are resolved relative to.
+    #[must_use]
+    pub fn path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
     }
 
     /// # Errors
     /// This function will return an error if the program cannot be run to completion successfully.
     pub fn start(self) -> Result<(), SplashRuntimeError<'a>> {
-        block::run(&self.program, &mut Context::new())?;
+        check(&self.program)?;
+
+        let mut context = Context::new();
+        if let Some(path) = self.path {
+            context.set_path(path);
+        }
+
+        block::run(&self.program, &mut context)?.returned()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nom::Parser;
+
+    use crate::parse::SplashParser;
+
+    use super::Runtime;
+
+    /// Runs a program, turning its errors into messages.
+    fn run(source: &str) -> Result<(), String> {
+        let source = format!("{{{source}}}");
+        let (_, program) = SplashParser
+            .parse(&source)
+            .map_err(|error| format!("{error:?}"))?;
+        Runtime::new(program)
+            .start()
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn calls_of_returned_and_indexed_functions() {
+        let program = r#"
+            fn make_adder(n) { return fn(x) { return x + n } }
+            let functions = [fn(x) { return x * 2 }]
+            if make_adder(1)(2) != 3 || functions[0](4) != 8 { throw "wrong result" }
+        "#;
+
+        assert_eq!(run(program), Ok(()));
+    }
+
+    #[test]
+    fn structures_without_fields() {
+        let program = r#"
+            struct Empty {}
+            let empty = Empty {}
+            if empty != Empty { } { throw "wrong result" }
+        "#;
+
+        assert_eq!(run(program), Ok(()));
+    }
+
+    #[test]
+    fn patterns_of_structures_with_the_same_shape() {
+        let program = r#"
+            struct Point { x }
+            fn other() {
+                struct Point { x }
+                return Point { x: 1 }
+            }
+            match other() {
+                Point { x } => { throw "matched another structure" }
+                _ => {}
+            }
+        "#;
+
+        assert_eq!(run(program), Ok(()));
+    }
+
+    #[test]
+    fn uncapitalized_structures() {
+        assert_eq!(
+            run("struct point { x }"),
+            Err(String::from(
+                "Struct 'point' must have a capitalized name to be constructed."
+            ))
+        );
+    }
+
+    #[test]
+    fn methods_from_function_fields() {
+        let program = r#"
+            struct Button { callback }
+            let button = Button { callback: fn(x) { return x + 1 } }
+            if button.callback(1) != 2 { throw "wrong result" }
+        "#;
+
+        assert_eq!(run(program), Ok(()));
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt::Display;
+
+use crate::parse::Identifier;
+
+use super::{value::Value, SplashRuntimeError};
+
+/// A user-defined record type, whose instances hold a value for each of its fields.
+#[derive(PartialEq, Debug)]
+pub struct Structure<'a> {
+    name: Identifier<'a>,
+    fields: Vec<Identifier<'a>>,
+}
+
+impl<'a> Structure<'a> {
+    pub fn new(name: Identifier<'a>, fields: Vec<Identifier<'a>>) -> Self {
+        Self { name, fields }
+    }
+
+    /// The struct of the errors caught by `catch` blocks.
+    pub fn error() -> Self {
+        Self::new("Error", vec!["kind", "message"])
+    }
+
+    pub fn name(&self) -> Identifier<'a> {
+        self.name
+    }
+
+    pub fn fields(&self) -> &[Identifier<'a>] {
+        &self.fields
+    }
+
+    /// Returns the position of `field` in the values of an instance.
+    pub fn field(&self, field: Identifier<'a>) -> Result<usize, SplashRuntimeError<'a>> {
+        self.fields
+            .iter()
+            .position(|&name| name == field)
+            .ok_or(SplashRuntimeError::UnknownField(self.name, field))
+    }
+
+    /// Orders the `values` given to each field by declaration order, checking that every field is given
+    /// exactly once.
+    pub fn instantiate(
+        &self,
+        values: Vec<(Identifier<'a>, Value<'a>)>,
+    ) -> Result<Vec<Value<'a>>, SplashRuntimeError<'a>> {
+        let mut ordered = vec![None; self.fields.len()];
+
+        for (field, value) in values {
+            let slot = &mut ordered[self.field(field)?];
+
+            if slot.is_some() {
+                return Err(SplashRuntimeError::DuplicateField(self.name, field));
+            }
+
+            *slot = Some(value);
+        }
+
+        ordered
+            .into_iter()
+            .zip(&self.fields)
+            .map(|(value, &field)| value.ok_or(SplashRuntimeError::MissingField(self.name, field)))
+            .collect()
+    }
+}
+
+impl Display for Structure<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<struct {}>", self.name)
+    }
+}
@@ -1,8 +1,8 @@
 use itertools::Itertools;
 
-use crate::parse::Expression;
+use crate::parse::{Accessor, Expression, Identifier};
 
-use super::{context::Context, evaluate::evaluate, value::Value, SplashRuntimeError};
+use super::{context::Context, evaluate::evaluate, key::Key, value::Value, SplashRuntimeError};
 
 pub fn evaluate_predicate<'a>(
     expression: &Expression<'a>,
@@ -20,7 +20,7 @@ pub fn evaluate_predicate<'a>(
 pub fn evaluate_values<'a>(
     expressions: &[Expression<'a>],
     context: &mut Context<'a>,
-) -> Result<Vec<Value>, SplashRuntimeError<'a>> {
+) -> Result<Vec<Value<'a>>, SplashRuntimeError<'a>> {
     expressions
         .iter()
         .map(|expression| evaluate(expression, context))
@@ -28,3 +28,198 @@ pub fn evaluate_values<'a>(
         .flatten_ok()
         .collect::<Result<Vec<_>, _>>()
 }
+
+/// An evaluated `Accessor`.
+pub enum Access<'a> {
+    Index(Value<'a>),
+    Field(Identifier<'a>),
+}
+
+pub fn evaluate_accessors<'a>(
+    accessors: &[Accessor<'a>],
+    context: &mut Context<'a>,
+) -> Result<Vec<Access<'a>>, SplashRuntimeError<'a>> {
+    accessors
+        .iter()
+        .map(|accessor| match accessor {
+            Accessor::Index(index) => evaluate(index, context)?
+                .ok_or(SplashRuntimeError::NoValue)
+                .map(Access::Index),
+            Accessor::Field(field) => Ok(Access::Field(field)),
+        })
+        .collect()
+}
+
+pub fn list_index<'a>(index: Value<'a>, length: usize) -> Result<usize, SplashRuntimeError<'a>> {
+    let index = match index {
+        Value::Integer(integer) => integer,
+        value => return Err(SplashRuntimeError::NotAnIndex(value)),
+    };
+
+    match usize::try_from(index) {
+        Ok(position) if position < length => Ok(position),
+        _ => Err(SplashRuntimeError::OutOufRange(Value::Integer(index))),
+    }
+}
+
+/// Returns the element of a list or map `collection` at `index`.
+pub fn index<'a>(
+    collection: Value<'a>,
+    index: Value<'a>,
+) -> Result<Value<'a>, SplashRuntimeError<'a>> {
+    match collection {
+        Value::List(mut list) => {
+            let index = list_index(index, list.len())?;
+            Ok(list.swap_remove(index))
+        }
+        Value::Map(mut map) => {
+            let key = Key::try_from(index)?;
+            map.remove(&key)
+                .ok_or_else(|| SplashRuntimeError::MissingKey(key.into()))
+        }
+        Value::Range(range) => {
+            let index = list_index(index, range.len())?;
+            Ok(Value::Integer(
+                range
+                    .get(index)
+                    .expect("the index should be within the range"),
+            ))
+        }
+        value => Err(SplashRuntimeError::NotIndexable(value)),
+    }
+}
+
+/// Returns the value of `field` in a struct instance, the top-level `field` of a module, or the variant `field`
+/// of an enum if it holds no values.
+pub fn field<'a>(
+    instance: Value<'a>,
+    field: Identifier<'a>,
+) -> Result<Value<'a>, SplashRuntimeError<'a>> {
+    match instance {
+        Value::Instance(structure, mut values) => {
+            let position = structure.field(field)?;
+            Ok(values.swap_remove(position))
+        }
+        Value::Module(module) => module.member(field),
+        Value::Enumeration(enumeration) => {
+            let variant = enumeration.variant(field)?;
+            let values = enumeration.instantiate(variant, Vec::new())?;
+            Ok(Value::Variant(enumeration, variant, values))
+        }
+        value => Err(SplashRuntimeError::NoField(value, field)),
+    }
+}
+
+fn field_mut<'a, 'b>(
+    instance: &'b mut Value<'a>,
+    field: Identifier<'a>,
+) -> Result<&'b mut Value<'a>, SplashRuntimeError<'a>> {
+    match instance {
+        Value::Instance(structure, values) => {
+            let position = structure.field(field)?;
+            Ok(&mut values[position])
+        }
+        value => Err(SplashRuntimeError::NoField(value.clone(), field)),
+    }
+}
+
+/// Returns a mutable reference to the element of a list or map `collection` at `index`.
+fn element_mut<'a, 'b>(
+    collection: &'b mut Value<'a>,
+    index: Value<'a>,
+) -> Result<&'b mut Value<'a>, SplashRuntimeError<'a>> {
+    match collection {
+        Value::List(list) => {
+            let index = list_index(index, list.len())?;
+            Ok(&mut list[index])
+        }
+        Value::Map(map) => {
+            let key = Key::try_from(index)?;
+            match map.get_mut(&key) {
+                Some(element) => Ok(element),
+                None => Err(SplashRuntimeError::MissingKey(key.into())),
+            }
+        }
+        collection => Err(SplashRuntimeError::NotIndexable(collection.clone())),
+    }
+}
+
+/// Attaches the `level` at which an `error` happened when indexing through `depth` nested collections.
+pub fn at_level(
+    error: SplashRuntimeError<'_>,
+    level: usize,
+    depth: usize,
+) -> SplashRuntimeError<'_> {
+    if depth > 1 {
+        SplashRuntimeError::IndexLevel(level, Box::new(error))
+    } else {
+        error
+    }
+}
+
+/// Replaces the element of a list `collection` at `index`, or inserts it if `collection` is a map.
+pub fn set_index<'a>(
+    collection: &mut Value<'a>,
+    index: Value<'a>,
+    value: Value<'a>,
+) -> Result<(), SplashRuntimeError<'a>> {
+    match collection {
+        Value::List(list) => {
+            let index = list_index(index, list.len())?;
+            list[index] = value;
+        }
+        Value::Map(map) => {
+            map.insert(Key::try_from(index)?, value);
+        }
+        collection => return Err(SplashRuntimeError::NotIndexable(collection.clone())),
+    }
+
+    Ok(())
+}
+
+/// Replaces the element of nested values designated by successive `accesses`, in place.
+pub fn set_nested<'a>(
+    value: &mut Value<'a>,
+    mut accesses: Vec<Access<'a>>,
+    element: Value<'a>,
+) -> Result<(), SplashRuntimeError<'a>> {
+    let depth = accesses.len();
+    let last = accesses.pop().expect("there should be at least one access");
+
+    let mut value = value;
+    for (level, access) in accesses.into_iter().enumerate() {
+        value = match access {
+            Access::Index(index) => element_mut(value, index),
+            Access::Field(field) => field_mut(value, field),
+        }
+        .map_err(|error| at_level(error, level + 1, depth))?;
+    }
+
+    match last {
+        Access::Index(index) => set_index(value, index, element),
+        Access::Field(field) => field_mut(value, field).map(|value| *value = element),
+    }
+    .map_err(|error| at_level(error, depth, depth))
+}
+
+/// Replaces the element of nested values designated by successive `accesses` with the result of `update`
+/// applied to it, in place. Unlike `set_nested`, the element must already exist.
+pub fn update_nested<'a>(
+    value: &mut Value<'a>,
+    accesses: Vec<Access<'a>>,
+    update: impl FnOnce(Value<'a>) -> Result<Value<'a>, SplashRuntimeError<'a>>,
+) -> Result<(), SplashRuntimeError<'a>> {
+    let depth = accesses.len();
+
+    let mut value = value;
+    for (level, access) in accesses.into_iter().enumerate() {
+        value = match access {
+            Access::Index(index) => element_mut(value, index),
+            Access::Field(field) => field_mut(value, field),
+        }
+        .map_err(|error| at_level(error, level + 1, depth))?;
+    }
+
+    *value = update(value.clone())?;
+    Ok(())
+}
@@ -1,18 +1,73 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display, rc::Rc};
 
 use crate::parse::Literal;
 
-#[derive(PartialEq, Clone, Debug)]
-pub enum Value {
+use super::{
+    enumeration::Enumeration, function::Function, key::Key, module::Module, range::Range,
+    structure::Structure,
+};
+
+#[derive(Clone, Debug)]
+pub enum Value<'a> {
+    Nil,
+    Integer(i64),
     Number(f64),
     Boolean(bool),
     String(String),
-    List(Vec<Value>),
+    List(Vec<Value<'a>>),
+    Map(BTreeMap<Key, Value<'a>>),
+    Range(Range),
+    Function(Rc<Function<'a>>),
+    Structure(Rc<Structure<'a>>),
+    Instance(Rc<Structure<'a>>, Vec<Value<'a>>),
+    Enumeration(Rc<Enumeration<'a>>),
+    /// An enum, the position of one of its variants and the variant's values.
+    Variant(Rc<Enumeration<'a>>, usize, Vec<Value<'a>>),
+    Module(Rc<Module<'a>>),
+}
+
+/// Integers and floats are compared by numeric value, other values structurally.
+impl PartialEq for Value<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Nil, Self::Nil) => true,
+            (Self::Integer(left), Self::Integer(right)) => left == right,
+            (Self::Number(left), Self::Number(right)) => left == right,
+            (Self::Integer(integer), Self::Number(number))
+            | (Self::Number(number), Self::Integer(integer)) => *integer as f64 == *number,
+            (Self::Boolean(left), Self::Boolean(right)) => left == right,
+            (Self::String(left), Self::String(right)) => left == right,
+            (Self::List(left), Self::List(right)) => left == right,
+            (Self::Map(left), Self::Map(right)) => left == right,
+            (Self::Range(left), Self::Range(right)) => left == right,
+            (Self::Range(range), Self::List(list)) | (Self::List(list), Self::Range(range)) => {
+                range.len() == list.len()
+                    && range
+                        .iter()
+                        .zip(list)
+                        .all(|(integer, element)| Self::Integer(integer) == *element)
+            }
+            (Self::Function(left), Self::Function(right)) => left == right,
+            (Self::Structure(left), Self::Structure(right)) => left == right,
+            (Self::Instance(left, left_values), Self::Instance(right, right_values)) => {
+                left == right && left_values == right_values
+            }
+            (Self::Enumeration(left), Self::Enumeration(right)) => left == right,
+            (
+                Self::Variant(left, left_variant, left_values),
+                Self::Variant(right, right_variant, right_values),
+            ) => left == right && left_variant == right_variant && left_values == right_values,
+            (Self::Module(left), Self::Module(right)) => left == right,
+            _ => false,
+        }
+    }
 }
 
-impl From<Literal> for Value {
+impl From<Literal> for Value<'_> {
     fn from(value: Literal) -> Self {
         match value {
+            Literal::Nil => Value::Nil,
+            Literal::Integer(integer) => Value::Integer(integer),
             Literal::Number(number) => Value::Number(number),
             Literal::Boolean(boolean) => Value::Boolean(boolean),
             Literal::String(string) => Value::String(string),
@@ -20,9 +75,11 @@ impl From<Literal> for Value {
     }
 }
 
-impl Display for Value {
+impl Display for Value<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Nil => write!(f, "nil"),
+            Self::Integer(integer) => integer.fmt(f),
             Self::Number(number) => number.fmt(f),
             Self::Boolean(boolean) => boolean.fmt(f),
             Self::String(string) => string.fmt(f),
@@ -37,6 +94,53 @@ impl Display for Value {
                         .join(", ")
                 )
             ),
+            Self::Map(map) => write!(
+                f,
+                "#{{{}}}",
+                map.iter()
+                    .map(|(key, value)| format!("{key}: {value}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Range(range) => range.fmt(f),
+            Self::Function(function) => function.fmt(f),
+            Self::Structure(structure) => structure.fmt(f),
+            Self::Instance(structure, values) => write!(
+                f,
+                "{} {{ {} }}",
+                structure.name(),
+                structure
+                    .fields()
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| format!("{field}: {value}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Enumeration(enumeration) => enumeration.fmt(f),
+            Self::Module(module) => module.fmt(f),
+            Self::Variant(enumeration, variant, values) => {
+                write!(
+                    f,
+                    "{}.{}",
+                    enumeration.name(),
+                    enumeration.variant_name(*variant)
+                )?;
+
+                if values.is_empty() {
+                    Ok(())
+                } else {
+                    write!(
+                        f,
+                        "({})",
+                        values
+                            .iter()
+                            .map(|value| format!("{value}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+            }
         }
     }
 }